@@ -0,0 +1,382 @@
+use futures::{stream, Stream, TryStreamExt};
+use http::{header::HeaderName, HeaderValue, Method};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    http_client::{SignAndDispatch, SignedRequest},
+    xml, AddressingStyle, BucketInfo, BucketStat, BucketSummary, Error, ListBucketsOptions,
+    ListBucketsOutput, OSSClient, PutBucketOptions, Result,
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct CreateBucketConfiguration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    storage_class: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data_redundancy_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct BucketInfoResult {
+    bucket: BucketInfo,
+}
+
+impl<C: SignAndDispatch + Send + Sync> OSSClient<C> {
+    /// One page of the buckets of the account, see `list_buckets_stream` for
+    /// all of them. The bucket of the client, if any, plays no part.
+    pub async fn list_buckets(&self, options: ListBucketsOptions) -> Result<ListBucketsOutput> {
+        let mut rqst = self.request_on(Method::GET, "")?;
+        let params = [("prefix", &options.prefix), ("marker", &options.marker)];
+        for (k, v) in params.iter() {
+            if let Some(v) = v {
+                rqst.add_params(*k, v.as_str());
+            }
+        }
+        if let Some(max_keys) = options.max_keys {
+            rqst.add_params("max-keys", max_keys.to_string().as_str());
+        }
+        xml::from_response(self.send(rqst).await?).await
+    }
+
+    /// Every bucket matching `options`, fetching the pages as it goes. A
+    /// truncated page whose next marker does not advance ends it with an
+    /// error.
+    pub fn list_buckets_stream(
+        &self,
+        options: ListBucketsOptions,
+    ) -> impl Stream<Item = Result<BucketSummary>> + '_ {
+        stream::try_unfold(Some(options), move |options| async move {
+            let options = match options {
+                Some(options) => options,
+                None => return Ok::<_, Error>(None),
+            };
+            let page = self.list_buckets(options.clone()).await?;
+            let next = if page.is_truncated {
+                let marker = options.marker.as_deref().unwrap_or_default();
+                if page.next_marker.is_empty() || page.next_marker == marker {
+                    return Err(Error::stuck_listing(marker));
+                }
+                Some(ListBucketsOptions {
+                    marker: Some(page.next_marker),
+                    ..options
+                })
+            } else {
+                None
+            };
+            Ok(Some((stream::iter(page.buckets.into_iter().map(Ok)), next)))
+        })
+        .try_flatten()
+    }
+
+    /// Creates `bucket` in the region of the client. Creating a bucket the
+    /// account already owns succeeds without changing it.
+    pub async fn put_bucket(&self, bucket: &str, options: PutBucketOptions) -> Result<()> {
+        let body = xml::to_string(&CreateBucketConfiguration {
+            storage_class: options.storage_class.map(|c| c.to_string()),
+            data_redundancy_type: options.data_redundancy_type.map(|t| t.to_string()),
+        })?;
+        let mut rqst = self.bucket_request(Method::PUT, bucket)?;
+        if let Some(acl) = options.acl {
+            rqst.add_header(
+                HeaderName::from_static("x-oss-acl"),
+                HeaderValue::from_str(&acl.to_string())?,
+            );
+        }
+        rqst.load(body);
+        self.send(rqst).await?;
+        Ok(())
+    }
+
+    /// Deletes `bucket`, which must hold neither objects nor multipart
+    /// uploads.
+    pub async fn delete_bucket(&self, bucket: &str) -> Result<()> {
+        self.send(self.bucket_request(Method::DELETE, bucket)?)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_bucket_info(&self, bucket: &str) -> Result<BucketInfo> {
+        let mut rqst = self.bucket_request(Method::GET, bucket)?;
+        rqst.add_params("bucketInfo", None);
+        let result: BucketInfoResult = xml::from_response(self.send(rqst).await?).await?;
+        Ok(result.bucket)
+    }
+
+    /// `oss-` and the region id of `bucket`, e.g. `oss-cn-hangzhou`.
+    pub async fn get_bucket_location(&self, bucket: &str) -> Result<String> {
+        let mut rqst = self.bucket_request(Method::GET, bucket)?;
+        rqst.add_params("location", None);
+        xml::from_response(self.send(rqst).await?).await
+    }
+
+    pub async fn get_bucket_stat(&self, bucket: &str) -> Result<BucketStat> {
+        let mut rqst = self.bucket_request(Method::GET, bucket)?;
+        rqst.add_params("stat", None);
+        xml::from_response(self.send(rqst).await?).await
+    }
+
+    /// A request on `bucket` rather than the bucket of the client.
+    fn bucket_request(&self, method: Method, bucket: &str) -> Result<SignedRequest> {
+        if bucket.is_empty() {
+            return Err(Error::config("a bucket name is required"));
+        }
+        self.request_on(method, bucket)
+    }
+
+    /// A request on `bucket`, on the service itself if empty. A CNAME domain
+    /// only serves the bucket bound to it, so on a CNAME client such requests
+    /// go to the OSS endpoint of the region instead, which must be an OSS one.
+    fn request_on(&self, method: Method, bucket: &str) -> Result<SignedRequest> {
+        let mut rqst = match method {
+            Method::PUT => self.put_request("", Vec::new()),
+            Method::DELETE => self.del_request(""),
+            _ => self.get_request(None),
+        };
+        rqst.bucket = bucket.to_owned();
+        if rqst.get_addressing_style() == AddressingStyle::Cname {
+            rqst.region = self.region.name().parse().map_err(|_| {
+                Error::config(format!(
+                    "no OSS endpoint for the CNAME {}: {:?} is not an OSS region",
+                    self.region.endpoint(),
+                    self.region.name()
+                ))
+            })?;
+            rqst.set_addressing_style(AddressingStyle::VirtualHosted);
+        }
+        Ok(rqst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_util::{mock_client, MockRequest, MockResponse, MockServer},
+        Acl, DataRedundancyType, Region, StorageClass,
+    };
+
+    fn bucket(name: &str) -> String {
+        format!(
+            "<Bucket><CreationDate>2014-05-14T11:18:32.000Z</CreationDate>\
+             <ExtranetEndpoint>oss-cn-hangzhou.aliyuncs.com</ExtranetEndpoint>\
+             <IntranetEndpoint>oss-cn-hangzhou-internal.aliyuncs.com</IntranetEndpoint>\
+             <Location>oss-cn-hangzhou</Location><Name>{}</Name><Region>cn-hangzhou</Region>\
+             <StorageClass>IA</StorageClass></Bucket>",
+            name
+        )
+    }
+
+    fn bucket_server(rqst: &MockRequest) -> MockResponse {
+        match (rqst.method.as_str(), rqst.path.as_str(), rqst.query.as_str()) {
+            ("GET", "/", _) => {
+                let (buckets, truncated) = match rqst.param("marker").as_deref() {
+                    None => (bucket("bucket-1") + &bucket("bucket-2"), true),
+                    Some("bucket-2") => (bucket("bucket-3"), false),
+                    Some(_) => return MockResponse::status(400),
+                };
+                MockResponse::ok(format!(
+                    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+                     <ListAllMyBucketsResult><Prefix>bucket</Prefix><MaxKeys>2</MaxKeys>\
+                     <IsTruncated>{}</IsTruncated><NextMarker>bucket-2</NextMarker>\
+                     <Owner><ID>512**</ID><DisplayName>51264</DisplayName></Owner>\
+                     <Buckets>{}</Buckets></ListAllMyBucketsResult>",
+                    truncated, buckets
+                ))
+            }
+            ("PUT", "/new-bucket/", "") => MockResponse::ok(""),
+            ("DELETE", "/old-bucket/", "") => MockResponse::status(204),
+            ("GET", "/bucket/", "bucketInfo") => MockResponse::ok(
+                "<BucketInfo><Bucket><AccessMonitor>Enabled</AccessMonitor>\
+                 <CreationDate>2013-07-31T10:56:21.000Z</CreationDate>\
+                 <ExtranetEndpoint>oss-cn-hangzhou.aliyuncs.com</ExtranetEndpoint>\
+                 <IntranetEndpoint>oss-cn-hangzhou-internal.aliyuncs.com</IntranetEndpoint>\
+                 <Location>oss-cn-hangzhou</Location><StorageClass>Standard</StorageClass>\
+                 <TransferAcceleration>Disabled</TransferAcceleration>\
+                 <CrossRegionReplication>Disabled</CrossRegionReplication>\
+                 <Name>bucket</Name><ResourceGroupId>rg-aek27tc****</ResourceGroupId>\
+                 <Owner><DisplayName>username</DisplayName><ID>27183473914****</ID></Owner>\
+                 <AccessControlList><Grant>public-read</Grant></AccessControlList>\
+                 <Comment>test</Comment><DataRedundancyType>ZRS</DataRedundancyType>\
+                 <Versioning>Enabled</Versioning>\
+                 <ServerSideEncryptionRule><SSEAlgorithm>None</SSEAlgorithm></ServerSideEncryptionRule>\
+                 </Bucket></BucketInfo>",
+            ),
+            ("GET", "/bucket/", "location") => {
+                MockResponse::ok("<LocationConstraint>oss-cn-hangzhou</LocationConstraint>")
+            }
+            ("GET", "/bucket/", "stat") => MockResponse::ok(
+                "<BucketStat><Storage>1600</Storage><ObjectCount>230</ObjectCount>\
+                 <MultipartUploadCount>40</MultipartUploadCount><LiveChannelCount>4</LiveChannelCount>\
+                 <LastModifiedTime>1643341269</LastModifiedTime><StandardStorage>430</StandardStorage>\
+                 <StandardObjectCount>66</StandardObjectCount><InfrequentAccessStorage>2359296</InfrequentAccessStorage>\
+                 <InfrequentAccessRealStorage>360</InfrequentAccessRealStorage>\
+                 <InfrequentAccessObjectCount>54</InfrequentAccessObjectCount></BucketStat>",
+            ),
+            _ => MockResponse::status(404),
+        }
+    }
+
+    #[tokio::test]
+    async fn list_buckets_test() {
+        let server = MockServer::start(bucket_server).await;
+        let oss_instance = mock_client(&server);
+
+        let page = oss_instance
+            .list_buckets(ListBucketsOptions {
+                prefix: Some("bucket".to_owned()),
+                max_keys: Some(2),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(page.is_truncated);
+        assert_eq!(page.next_marker, "bucket-2");
+        assert_eq!(page.owner.as_ref().unwrap().id, "512**");
+        let bucket = &page.buckets[0];
+        assert_eq!(bucket.name, "bucket-1");
+        assert_eq!(bucket.region, "cn-hangzhou");
+        assert_eq!(bucket.location, "oss-cn-hangzhou");
+        assert_eq!(bucket.storage_class, Some(StorageClass::InfrequentAccess));
+
+        let rqst = &server.requests()[0];
+        assert_eq!(rqst.param("prefix").as_deref(), Some("bucket"));
+        assert_eq!(rqst.param("max-keys").as_deref(), Some("2"));
+
+        let names: Vec<String> = oss_instance
+            .list_buckets_stream(ListBucketsOptions::default())
+            .map_ok(|bucket| bucket.name)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(names, vec!["bucket-1", "bucket-2", "bucket-3"]);
+    }
+
+    #[tokio::test]
+    async fn stuck_list_buckets_test() {
+        // Every page is truncated, and the marker stops at bucket-2
+        let server = MockServer::start(|_| {
+            MockResponse::ok(format!(
+                "<ListAllMyBucketsResult><IsTruncated>true</IsTruncated>\
+                 <NextMarker>bucket-2</NextMarker><Buckets>{}</Buckets>\
+                 </ListAllMyBucketsResult>",
+                bucket("bucket-2")
+            ))
+        })
+        .await;
+        let oss_instance = mock_client(&server);
+        let err = oss_instance
+            .list_buckets_stream(ListBucketsOptions::default())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("bucket-2"), "{}", err);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn cname_test() {
+        let mut oss_instance =
+            OSSClient::new_with_reqwest("cn-hangzhou", "https", "bucket", "ak", "sk");
+        oss_instance.region = Region::custom("cn-hangzhou", "static.example.com");
+        oss_instance.set_addressing_style(AddressingStyle::Cname);
+        let url = |rqst: SignedRequest| rqst.generate_url().unwrap().to_string();
+
+        assert_eq!(
+            url(oss_instance.get_request("object")),
+            "https://static.example.com/object"
+        );
+        assert_eq!(
+            url(oss_instance.request_on(Method::GET, "").unwrap()),
+            "https://oss-cn-hangzhou.aliyuncs.com/"
+        );
+        assert_eq!(
+            url(oss_instance.bucket_request(Method::PUT, "other").unwrap()),
+            "https://other.oss-cn-hangzhou.aliyuncs.com/"
+        );
+
+        // No OSS endpoint to fall back on
+        oss_instance.region = Region::custom("my-cdn", "static.example.com");
+        let err = oss_instance
+            .bucket_request(Method::PUT, "other")
+            .unwrap_err();
+        assert!(err.to_string().contains("static.example.com"), "{}", err);
+        assert!(oss_instance.request_on(Method::GET, "").is_err());
+    }
+
+    #[tokio::test]
+    async fn empty_bucket_test() {
+        let server = MockServer::start(bucket_server).await;
+        let oss_instance = mock_client(&server);
+        let errors = [
+            oss_instance
+                .put_bucket("", PutBucketOptions::default())
+                .await
+                .unwrap_err(),
+            oss_instance.delete_bucket("").await.unwrap_err(),
+            oss_instance.get_bucket_info("").await.unwrap_err(),
+            oss_instance.get_bucket_location("").await.unwrap_err(),
+            oss_instance.get_bucket_stat("").await.unwrap_err(),
+        ];
+        for err in errors {
+            assert!(
+                err.to_string().contains("bucket name is required"),
+                "{}",
+                err
+            );
+        }
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn bucket_test() {
+        let server = MockServer::start(bucket_server).await;
+        let oss_instance = mock_client(&server);
+
+        oss_instance
+            .put_bucket(
+                "new-bucket",
+                PutBucketOptions {
+                    storage_class: Some(StorageClass::InfrequentAccess),
+                    data_redundancy_type: Some(DataRedundancyType::Zrs),
+                    acl: Some(Acl::PublicRead),
+                },
+            )
+            .await
+            .unwrap();
+        let rqst = &server.requests()[0];
+        assert_eq!(rqst.header("x-oss-acl"), Some("public-read"));
+        assert_eq!(
+            String::from_utf8_lossy(&rqst.body),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+             <CreateBucketConfiguration><StorageClass>IA</StorageClass>\
+             <DataRedundancyType>ZRS</DataRedundancyType></CreateBucketConfiguration>"
+        );
+        oss_instance.delete_bucket("old-bucket").await.unwrap();
+        assert!(oss_instance
+            .delete_bucket("missing")
+            .await
+            .unwrap_err()
+            .is_not_found());
+
+        let info = oss_instance.get_bucket_info("bucket").await.unwrap();
+        assert_eq!(info.name, "bucket");
+        assert_eq!(info.acl, Some(Acl::PublicRead));
+        assert_eq!(info.data_redundancy_type, Some(DataRedundancyType::Zrs));
+        assert_eq!(info.storage_class, Some(StorageClass::Standard));
+        assert_eq!(info.owner.unwrap().display_name, "username");
+        assert_eq!(info.versioning.as_deref(), Some("Enabled"));
+        assert_eq!(info.comment, "test");
+
+        let location = oss_instance.get_bucket_location("bucket").await.unwrap();
+        assert_eq!(location, "oss-cn-hangzhou");
+
+        let stat = oss_instance.get_bucket_stat("bucket").await.unwrap();
+        assert_eq!(stat.storage, 1600);
+        assert_eq!(stat.object_count, 230);
+        assert_eq!(stat.last_modified_time, 1643341269);
+        assert_eq!(stat.infrequent_access_real_storage, 360);
+        assert_eq!(stat.archive_storage, 0);
+    }
+}
@@ -0,0 +1,358 @@
+use std::sync::Arc;
+
+use http::{header::HeaderName, HeaderMap, HeaderValue};
+use reqwest::{tls, Certificate, Proxy};
+
+use crate::{
+    statics::reqwest_client_builder, AddressingStyle, ChainProvider, CredentialsProvider,
+    EndpointType, Error, OSSClient, OssClient, Region, Result, RetryPolicy, Schema,
+    SignatureVersion, StaticProvider, Timeouts,
+};
+
+/// Configures an `OssClient` and the reqwest client under it. Nothing is
+/// checked until `build`, which fails on the first invalid setting.
+///
+/// ```no_run
+/// # fn main() -> Result<(), oss_sdk::Error> {
+/// let client = oss_sdk::OSSClient::builder()
+///     .region("cn-hangzhou")
+///     .bucket("examplebucket")
+///     .credentials("access_key_id", "access_key_secret")
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct OSSClientBuilder {
+    credentials: Option<Arc<dyn CredentialsProvider>>,
+    region: Option<String>,
+    endpoint: Option<String>,
+    bucket: Option<String>,
+    schema: Option<String>,
+    endpoint_type: EndpointType,
+    addressing_style: AddressingStyle,
+    signature_version: SignatureVersion,
+    retry_policy: RetryPolicy,
+    timeouts: Timeouts,
+    proxy: Option<String>,
+    user_agent: Option<String>,
+    pool_max_idle_per_host: Option<usize>,
+    root_certificates: Vec<Vec<u8>>,
+    min_tls_version: Option<tls::Version>,
+    http1_only: bool,
+    http2_prior_knowledge: bool,
+    default_headers: Vec<(String, String)>,
+}
+
+impl OssClient {
+    pub fn builder() -> OSSClientBuilder {
+        OSSClientBuilder::default()
+    }
+}
+
+impl OSSClientBuilder {
+    /// A static access key pair.
+    pub fn credentials<S1, S2>(self, access_key_id: S1, access_key_secret: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        self.credentials_provider(StaticProvider::new(access_key_id, access_key_secret))
+    }
+    /// Where to get credentials from when signing, `ChainProvider::default()`
+    /// if not set.
+    pub fn credentials_provider<P>(mut self, provider: P) -> Self
+    where
+        P: CredentialsProvider + 'static,
+    {
+        self.credentials = Some(Arc::new(provider));
+        self
+    }
+    /// Region id such as `cn-hangzhou`, or any name `Region` parses.
+    pub fn region<S: Into<String>>(mut self, region: S) -> Self {
+        self.region = Some(region.into());
+        self
+    }
+    /// `host[:port]` of an OSS compatible service, see `Region::Custom`. The
+    /// region, if also set, is the id used to sign and need not be known;
+    /// otherwise it is taken from an endpoint of OSS itself, and V4 signing
+    /// fails to build without it. An `http://` or `https://` prefix sets the
+    /// schema, which must not conflict with `schema`.
+    pub fn endpoint<S: Into<String>>(mut self, endpoint: S) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+    /// Left out for the operations on the service, like listing buckets.
+    pub fn bucket<S: Into<String>>(mut self, bucket: S) -> Self {
+        self.bucket = Some(bucket.into());
+        self
+    }
+    /// `http` or `https`, the default unless the endpoint has a prefix.
+    pub fn schema<S: Into<String>>(mut self, schema: S) -> Self {
+        self.schema = Some(schema.into());
+        self
+    }
+    pub fn endpoint_type(mut self, endpoint_type: EndpointType) -> Self {
+        self.endpoint_type = endpoint_type;
+        self
+    }
+    pub fn addressing_style(mut self, addressing_style: AddressingStyle) -> Self {
+        self.addressing_style = addressing_style;
+        self
+    }
+    pub fn signature_version(mut self, signature_version: SignatureVersion) -> Self {
+        self.signature_version = signature_version;
+        self
+    }
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+    /// Url of a proxy all requests go through, e.g. `http://proxy:8080`.
+    pub fn proxy<S: Into<String>>(mut self, proxy: S) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+    pub fn user_agent<S: Into<String>>(mut self, user_agent: S) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+    /// Idle connections kept open per host, unbounded by default.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+    /// Trusts the PEM encoded certificate besides the system ones, e.g. the
+    /// CA of a private OSS deployment.
+    pub fn add_root_certificate_pem<B: Into<Vec<u8>>>(mut self, pem: B) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+    pub fn min_tls_version(mut self, version: tls::Version) -> Self {
+        self.min_tls_version = Some(version);
+        self
+    }
+    /// Never upgrade to HTTP/2.
+    pub fn http1_only(mut self) -> Self {
+        self.http1_only = true;
+        self.http2_prior_knowledge = false;
+        self
+    }
+    /// Speak HTTP/2 from the start, for endpoints known to support it.
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.http2_prior_knowledge = true;
+        self.http1_only = false;
+        self
+    }
+    /// Sent, and signed, with every request made by the client. Headers set
+    /// on a request take precedence.
+    pub fn default_header<K, V>(mut self, name: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.default_headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn build(self) -> Result<OssClient> {
+        let endpoint_schema = match self.endpoint.as_deref().and_then(|e| e.split_once("://")) {
+            Some((scheme, _)) => Some(scheme.parse::<Schema>()?),
+            None => None,
+        };
+        let schema = match (self.schema, endpoint_schema) {
+            (Some(schema), endpoint_schema) => {
+                let schema: Schema = schema.parse()?;
+                match endpoint_schema {
+                    Some(other) if other.to_string() != schema.to_string() => {
+                        return Err(Error::config(format!(
+                            "schema {} conflicts with the {} endpoint",
+                            schema, other
+                        )))
+                    }
+                    _ => schema,
+                }
+            }
+            (None, endpoint_schema) => endpoint_schema.unwrap_or_default(),
+        };
+        let region = match (self.endpoint, self.region) {
+            (Some(endpoint), Some(region)) => Region::custom(region, endpoint),
+            (Some(endpoint), None) => match endpoint.parse::<Region>() {
+                // An endpoint of OSS itself names its region
+                Ok(known) => Region::custom(known.name(), endpoint),
+                Err(_) if self.signature_version == SignatureVersion::V4 => {
+                    return Err(Error::config(format!(
+                        "signing with V4 needs the region of endpoint {}",
+                        endpoint
+                    )))
+                }
+                Err(_) => Region::custom("", endpoint),
+            },
+            (None, Some(region)) => region.parse()?,
+            (None, None) => return Err(Error::config("a region or an endpoint is required")),
+        };
+        let bucket = self.bucket.unwrap_or_default();
+        if !bucket.is_empty() && !is_bucket_name(&bucket) {
+            return Err(Error::config(format!("invalid bucket name: {}", bucket)));
+        }
+        let mut default_headers = HeaderMap::new();
+        for (name, value) in self.default_headers {
+            default_headers.insert(
+                HeaderName::from_bytes(name.as_bytes())?,
+                HeaderValue::from_str(&value)?,
+            );
+        }
+
+        let mut builder = reqwest_client_builder(&self.timeouts);
+        if let Some(proxy) = self.proxy {
+            builder = builder.proxy(Proxy::all(&proxy).map_err(Error::config)?);
+        }
+        if let Some(user_agent) = self.user_agent {
+            builder = builder.user_agent(HeaderValue::from_str(&user_agent)?);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        for pem in self.root_certificates {
+            builder =
+                builder.add_root_certificate(Certificate::from_pem(&pem).map_err(Error::config)?);
+        }
+        if let Some(version) = self.min_tls_version {
+            builder = builder.min_tls_version(version);
+        }
+        if self.http1_only {
+            builder = builder.http1_only();
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        let client = builder.build().map_err(Error::config)?;
+
+        let credentials = self
+            .credentials
+            .unwrap_or_else(|| Arc::new(ChainProvider::default()));
+        let mut oss_client = OSSClient::from_parts(client, region, schema, bucket, credentials);
+        oss_client.set_endpoint_type(self.endpoint_type);
+        oss_client.set_addressing_style(self.addressing_style);
+        oss_client.set_signature_version(self.signature_version);
+        oss_client.set_retry_policy(self.retry_policy);
+        oss_client.set_timeouts(self.timeouts);
+        oss_client.set_default_headers(default_headers);
+        Ok(oss_client)
+    }
+}
+
+/// 3 to 63 lowercase letters, digits and hyphens, starting and ending with a
+/// letter or a digit.
+fn is_bucket_name(name: &str) -> bool {
+    (3..=63).contains(&name.len())
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        && !name.starts_with('-')
+        && !name.ends_with('-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{MockResponse, MockServer};
+
+    #[test]
+    fn build_error_test() {
+        let builder = || OSSClient::builder().region("cn-hangzhou");
+        assert!(builder().build().is_ok());
+        assert!(OSSClient::builder().build().is_err());
+        assert!(OSSClient::builder().region("mars-1").build().is_err());
+        assert!(builder().schema("ftp").build().is_err());
+        assert!(builder().bucket("Bucket_1").build().is_err());
+        assert!(builder().bucket("-bucket").build().is_err());
+        assert!(builder().proxy("http://[::1").build().is_err());
+        assert!(builder().default_header("x-oss-a b", "c").build().is_err());
+        assert!(builder().user_agent("a\nb").build().is_err());
+        assert!(builder()
+            .add_root_certificate_pem("no pem")
+            .build()
+            .is_err());
+
+        let oss_instance = OSSClient::builder()
+            .region("cn-private-1")
+            .endpoint("https://oss.example.com/")
+            .bucket("bucket-1")
+            .build()
+            .unwrap();
+        assert_eq!(
+            oss_instance.region,
+            Region::custom("cn-private-1", "oss.example.com")
+        );
+        assert_eq!(oss_instance.get_bucket(), "bucket-1");
+
+        // V4 signs with the region, taken from the endpoint when not set
+        let v4 = |endpoint: &str| {
+            OSSClient::builder()
+                .endpoint(endpoint)
+                .signature_version(SignatureVersion::V4)
+                .build()
+        };
+        assert!(v4("oss.example.com").is_err());
+        let oss_instance = v4("oss-cn-shanghai-internal.aliyuncs.com").unwrap();
+        assert_eq!(oss_instance.region.name(), "cn-shanghai");
+        assert_eq!(
+            oss_instance.region.endpoint(),
+            "oss-cn-shanghai-internal.aliyuncs.com"
+        );
+
+        // The schema of the endpoint, unless it conflicts with the one set
+        let oss_instance = OSSClient::builder()
+            .endpoint("http://oss.example.com:9000")
+            .bucket("bucket")
+            .build()
+            .unwrap();
+        let url = oss_instance.get_request("object").generate_url().unwrap();
+        assert_eq!(url.as_str(), "http://bucket.oss.example.com:9000/object");
+        assert!(OSSClient::builder()
+            .endpoint("http://127.0.0.1:9000")
+            .schema("https")
+            .build()
+            .is_err());
+        assert!(OSSClient::builder()
+            .endpoint("ftp://127.0.0.1:9000")
+            .build()
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn default_headers_test() {
+        let server = MockServer::start(|_| MockResponse::ok("")).await;
+        let oss_instance = OSSClient::builder()
+            .endpoint(server.endpoint())
+            .schema("http")
+            .bucket("bucket")
+            .addressing_style(AddressingStyle::Path)
+            .credentials("ak", "sk")
+            .user_agent("oss-test/1.0")
+            .default_header("x-oss-request-payer", "requester")
+            .default_header("content-type", "text/plain")
+            .build()
+            .unwrap();
+
+        oss_instance
+            .send(oss_instance.get_request("object"))
+            .await
+            .unwrap();
+        let mut rqst = oss_instance.put_request("object", "body".to_owned());
+        rqst.set_content_type(HeaderValue::from_static("application/json"));
+        oss_instance.send(rqst).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/bucket/object");
+        assert_eq!(requests[0].header("user-agent"), Some("oss-test/1.0"));
+        assert_eq!(requests[0].header("x-oss-request-payer"), Some("requester"));
+        assert_eq!(requests[0].header("content-type"), Some("text/plain"));
+        assert_eq!(requests[1].header("content-type"), Some("application/json"));
+    }
+}
@@ -0,0 +1,52 @@
+//! JSON checkpoint files of resumable transfers.
+
+use std::{
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// `path` with `suffix` appended, e.g. `backup.tar.ossupload`.
+pub(crate) fn next_to(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(suffix);
+    path.into()
+}
+
+/// `None` when there is no checkpoint at `path` or it cannot be parsed, in
+/// which case the transfer starts over.
+pub(crate) async fn load<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let json = match tokio::fs::read(path).await {
+        Ok(json) => json,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            log::warn!("ignoring unreadable checkpoint {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    match serde_json::from_slice(&json) {
+        Ok(checkpoint) => Some(checkpoint),
+        Err(e) => {
+            log::warn!("ignoring corrupt checkpoint {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Replaces the checkpoint at `path` atomically, so a crash never leaves a
+/// truncated one behind.
+pub(crate) async fn save<T: Serialize>(path: &Path, checkpoint: &T) -> io::Result<()> {
+    let json = serde_json::to_vec(checkpoint)?;
+    let tmp = next_to(path, ".tmp");
+    tokio::fs::write(&tmp, json).await?;
+    tokio::fs::rename(&tmp, path).await
+}
+
+pub(crate) async fn remove(path: &Path) -> io::Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,422 @@
+use std::{env, fmt, fs, path::Path, time::Duration};
+
+#[cfg(feature = "serde-config")]
+use serde::Deserialize;
+
+use crate::{
+    credentials::profile::{ini_profile, parse_ini},
+    Credentials, EndpointType, Error, OSSClient, OSSClientBuilder, OssClient, Region, Result,
+    RetryPolicy, StaticProvider, Timeouts,
+};
+
+/// Settings of an `OssClient` read from outside the code, so that every
+/// service configures OSS the same way. Anything left unset keeps the default
+/// of `OSSClient::builder`, and without an access key the client falls back to
+/// `ChainProvider::default()`.
+///
+/// The same keys are read from every source, case and `-`/`_` aside: `region`,
+/// `endpoint`, `bucket`, `schema`, `access_key_id`, `access_key_secret`,
+/// `security_token`, `connect_timeout`, `request_timeout`, `read_timeout`,
+/// `max_attempts`, `retry_base_delay`, `retry_max_delay` and `retry_deadline`,
+/// durations in seconds.
+#[derive(Clone, Default, PartialEq)]
+pub struct OssConfig {
+    /// Region id, e.g. `cn-hangzhou`.
+    pub region: Option<String>,
+    /// An OSS endpoint such as `oss-cn-hangzhou.aliyuncs.com`, or the
+    /// `host[:port]` of an OSS compatible service. An `http://` prefix
+    /// switches the schema to http unless it is set.
+    pub endpoint: Option<String>,
+    pub bucket: Option<String>,
+    pub schema: Option<String>,
+    pub access_key_id: Option<String>,
+    pub access_key_secret: Option<String>,
+    pub security_token: Option<String>,
+    pub connect_timeout: Option<Duration>,
+    pub request_timeout: Option<Duration>,
+    pub read_timeout: Option<Duration>,
+    pub max_attempts: Option<u32>,
+    pub retry_base_delay: Option<Duration>,
+    pub retry_max_delay: Option<Duration>,
+    pub retry_deadline: Option<Duration>,
+}
+
+const KEYS: &[&str] = &[
+    "region",
+    "endpoint",
+    "bucket",
+    "schema",
+    "access_key_id",
+    "access_key_secret",
+    "security_token",
+    "connect_timeout",
+    "request_timeout",
+    "read_timeout",
+    "max_attempts",
+    "retry_base_delay",
+    "retry_max_delay",
+    "retry_deadline",
+];
+
+impl OssConfig {
+    /// Reads `OSS_<KEY>` for every key, e.g. `OSS_BUCKET` or
+    /// `OSS_CONNECT_TIMEOUT`, and the `ALIBABA_CLOUD_ACCESS_KEY_ID`,
+    /// `ALIBABA_CLOUD_ACCESS_KEY_SECRET`, `ALIBABA_CLOUD_SECURITY_TOKEN`,
+    /// `OSS_SESSION_TOKEN` and `OSS_DEFAULT_REGION` aliases the credential
+    /// providers and `Region::default()` read.
+    pub fn from_env() -> Result<Self> {
+        let mut config = Self::default();
+        let aliases = [
+            ("OSS_DEFAULT_REGION", "region"),
+            ("ALIBABA_CLOUD_ACCESS_KEY_ID", "access_key_id"),
+            ("ALIBABA_CLOUD_ACCESS_KEY_SECRET", "access_key_secret"),
+            ("ALIBABA_CLOUD_SECURITY_TOKEN", "security_token"),
+            ("OSS_SESSION_TOKEN", "security_token"),
+        ];
+        for (name, key) in aliases.iter() {
+            if let Some(value) = env::var(name).ok().filter(|v| !v.is_empty()) {
+                config.set(key, &value, name)?;
+            }
+        }
+        // Set last, so that they win over the aliases
+        for key in KEYS.iter() {
+            let name = format!("OSS_{}", key.to_ascii_uppercase());
+            if let Some(value) = env::var(&name).ok().filter(|v| !v.is_empty()) {
+                config.set(key, &value, &name)?;
+            }
+        }
+        Ok(config)
+    }
+
+    /// The profile named by `OSS_PROFILE`, or the default one, of the config
+    /// file at `path`, see `from_file_profile`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let profile = env::var("OSS_PROFILE").ok().filter(|p| !p.is_empty());
+        Self::from_file_profile(path, profile.as_deref())
+    }
+
+    /// The `profile` of the config file at `path`, the default one if `None`.
+    ///
+    /// Files are read as ossutil INI files, where the default profile is the
+    /// `[Credentials]` or `[default]` section and any other `[name]` or
+    /// `[profile name]`. ossutil's `accessKeyID`, `stsToken`,
+    /// `readwrite-timeout` and `retry-times` keys are understood. With the
+    /// `serde-config` feature, `.toml` and `.json` files hold a table of
+    /// profiles by name, `default` being the default one.
+    pub fn from_file_profile<P: AsRef<Path>>(path: P, profile: Option<&str>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if extension.eq_ignore_ascii_case("toml") || extension.eq_ignore_ascii_case("json") {
+            return Self::from_serde(&content, extension, path, profile);
+        }
+        let source = path.display().to_string();
+        let sections = parse_ini(&content);
+        let entries =
+            ini_profile(&sections, profile).ok_or_else(|| no_profile(&source, profile))?;
+        let mut config = Self::default();
+        for (key, value) in entries {
+            if !value.is_empty() {
+                config.set(key, value, &source)?;
+            }
+        }
+        Ok(config)
+    }
+
+    #[cfg(feature = "serde-config")]
+    fn from_serde(
+        content: &str,
+        extension: &str,
+        path: &Path,
+        profile: Option<&str>,
+    ) -> Result<Self> {
+        use std::collections::BTreeMap;
+
+        let source = path.display().to_string();
+        let invalid = |e: String| Error::config(format!("invalid {}: {}", source, e));
+        let mut profiles: BTreeMap<String, Self> = if extension.eq_ignore_ascii_case("toml") {
+            toml::from_str(content).map_err(|e| invalid(e.to_string()))?
+        } else {
+            serde_json::from_str(content).map_err(|e| invalid(e.to_string()))?
+        };
+        profiles
+            .remove(profile.unwrap_or("default"))
+            .ok_or_else(|| no_profile(&source, profile))
+    }
+
+    #[cfg(not(feature = "serde-config"))]
+    fn from_serde(_: &str, extension: &str, path: &Path, _: Option<&str>) -> Result<Self> {
+        Err(Error::config(format!(
+            "{} is a {} file, which needs the serde-config feature",
+            path.display(),
+            extension
+        )))
+    }
+
+    /// A builder taking every setting of the config, for the ones it has no
+    /// key for, like a proxy.
+    pub fn builder(&self) -> Result<OSSClientBuilder> {
+        let mut builder = OSSClient::builder();
+        let mut schema = self.schema.clone();
+        match (&self.region, &self.endpoint) {
+            (region, Some(endpoint)) => {
+                if schema.is_none() && endpoint.starts_with("http://") {
+                    schema = Some("http".to_owned());
+                }
+                // An endpoint of OSS itself names its region
+                match endpoint.parse::<Region>() {
+                    Ok(known) if region.is_none() => {
+                        builder = builder.region(known.name());
+                        if endpoint.contains(known.endpoint_of(EndpointType::Internal)) {
+                            builder = builder.endpoint_type(EndpointType::Internal);
+                        }
+                    }
+                    _ => {
+                        builder = builder.endpoint(endpoint.as_str());
+                        if let Some(region) = region {
+                            builder = builder.region(region.as_str());
+                        }
+                    }
+                }
+            }
+            (Some(region), None) => builder = builder.region(region.as_str()),
+            (None, None) => {}
+        }
+        if let Some(ref bucket) = self.bucket {
+            builder = builder.bucket(bucket.as_str());
+        }
+        if let Some(schema) = schema {
+            builder = builder.schema(schema);
+        }
+        match (&self.access_key_id, &self.access_key_secret) {
+            (Some(id), Some(secret)) => {
+                let token = self.security_token.clone().unwrap_or_default();
+                let credentials = Credentials::new_with_token(id, secret, token, None);
+                builder = builder.credentials_provider(StaticProvider::from(credentials));
+            }
+            (None, None) => {}
+            _ => {
+                return Err(Error::config(
+                    "access_key_id and access_key_secret go together",
+                ))
+            }
+        }
+        let defaults = Timeouts::default();
+        builder = builder.timeouts(Timeouts {
+            connect: self.connect_timeout.or(defaults.connect),
+            request: self.request_timeout.or(defaults.request),
+            read: self.read_timeout.or(defaults.read),
+        });
+        let defaults = RetryPolicy::default();
+        builder = builder.retry_policy(RetryPolicy {
+            max_attempts: self.max_attempts.unwrap_or(defaults.max_attempts),
+            base_delay: self.retry_base_delay.unwrap_or(defaults.base_delay),
+            max_delay: self.retry_max_delay.unwrap_or(defaults.max_delay),
+            deadline: self.retry_deadline.or(defaults.deadline),
+            ..defaults
+        });
+        Ok(builder)
+    }
+
+    pub fn build(&self) -> Result<OssClient> {
+        self.builder()?.build()
+    }
+
+    /// Sets `key`, compared after `normalize`, to `value` read from
+    /// `source`. Unknown keys are ignored.
+    fn set(&mut self, key: &str, value: &str, source: &str) -> Result<()> {
+        let value = value.trim();
+        let invalid = || Error::config(format!("invalid {} in {}: {}", key, source, value));
+        let seconds = || -> Result<Duration> {
+            value
+                .parse::<f64>()
+                .ok()
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .ok_or_else(invalid)
+        };
+        match normalize(key).as_str() {
+            "region" => self.region = Some(value.to_owned()),
+            "endpoint" => self.endpoint = Some(value.to_owned()),
+            "bucket" => self.bucket = Some(value.to_owned()),
+            "schema" => self.schema = Some(value.to_owned()),
+            "accesskeyid" => self.access_key_id = Some(value.to_owned()),
+            "accesskeysecret" => self.access_key_secret = Some(value.to_owned()),
+            "securitytoken" | "ststoken" | "sessiontoken" => {
+                self.security_token = Some(value.to_owned())
+            }
+            "connecttimeout" => self.connect_timeout = Some(seconds()?),
+            "requesttimeout" => self.request_timeout = Some(seconds()?),
+            "readtimeout" | "readwritetimeout" => self.read_timeout = Some(seconds()?),
+            "maxattempts" => self.max_attempts = Some(value.parse().map_err(|_| invalid())?),
+            "retrytimes" => {
+                let retries: u32 = value.parse().map_err(|_| invalid())?;
+                self.max_attempts = Some(retries.saturating_add(1));
+            }
+            "retrybasedelay" => self.retry_base_delay = Some(seconds()?),
+            "retrymaxdelay" => self.retry_max_delay = Some(seconds()?),
+            "retrydeadline" => self.retry_deadline = Some(seconds()?),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Lowercase without `-` and `_`, so that `accessKeyID`, `access_key_id` and
+/// `ACCESS_KEY_ID` are the same key.
+fn normalize(key: &str) -> String {
+    key.chars()
+        .filter(|c| *c != '-' && *c != '_')
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn no_profile(source: &str, profile: Option<&str>) -> Error {
+    Error::config(format!(
+        "no profile {} in {}",
+        profile.unwrap_or("default"),
+        source
+    ))
+}
+
+/// A table of keys, read like any other source.
+#[cfg(feature = "serde-config")]
+impl<'de> Deserialize<'de> for OssConfig {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Value {
+            Integer(i64),
+            Float(f64),
+            String(String),
+        }
+
+        let entries = std::collections::BTreeMap::<String, Value>::deserialize(deserializer)?;
+        let mut config = Self::default();
+        for (key, value) in entries {
+            let value = match value {
+                Value::Integer(i) => i.to_string(),
+                Value::Float(f) => f.to_string(),
+                Value::String(s) => s,
+            };
+            config
+                .set(&key, &value, "the config")
+                .map_err(serde::de::Error::custom)?;
+        }
+        Ok(config)
+    }
+}
+
+impl fmt::Debug for OssConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let redacted = |s: &Option<String>| s.as_ref().map(|_| "** redacted **");
+        f.debug_struct("OssConfig")
+            .field("region", &self.region)
+            .field("endpoint", &self.endpoint)
+            .field("bucket", &self.bucket)
+            .field("schema", &self.schema)
+            .field("access_key_id", &self.access_key_id)
+            .field("access_key_secret", &redacted(&self.access_key_secret))
+            .field("security_token", &redacted(&self.security_token))
+            .field("connect_timeout", &self.connect_timeout)
+            .field("request_timeout", &self.request_timeout)
+            .field("read_timeout", &self.read_timeout)
+            .field("max_attempts", &self.max_attempts)
+            .field("retry_base_delay", &self.retry_base_delay)
+            .field("retry_max_delay", &self.retry_max_delay)
+            .field("retry_deadline", &self.retry_deadline)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(name: &str, content: &str) -> std::path::PathBuf {
+        let path = crate::test_util::temp_path(name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn ini_test() {
+        let path = write(
+            "ossutilconfig",
+            "[Credentials]\nlanguage=EN\nendpoint=http://oss-cn-shanghai-internal.aliyuncs.com\n\
+             accessKeyID=id\naccessKeySecret=secret\n\
+             [profile staging]\nregion = cn-private-1\nendpoint = oss.example.com:8080\n\
+             bucket = staging-bucket\naccess-key-id = id\naccess-key-secret = secret\n\
+             stsToken = sts-token\nconnect-timeout = 2.5\nreadwrite-timeout = 30\nretry-times = 4\n",
+        );
+
+        let config = OssConfig::from_file_profile(&path, None).unwrap();
+        assert_eq!(config.access_key_id.as_deref(), Some("id"));
+        let oss_instance = config.build().unwrap();
+        assert_eq!(oss_instance.region, Region::ShangHai);
+        assert_eq!(oss_instance.get_endpoint_type(), EndpointType::Internal);
+        let url = oss_instance.get_request("object").generate_url().unwrap();
+        assert!(url.as_str().starts_with("http://"));
+
+        let config = OssConfig::from_file_profile(&path, Some("staging")).unwrap();
+        assert_eq!(config.security_token.as_deref(), Some("sts-token"));
+        assert_eq!(config.connect_timeout, Some(Duration::from_millis(2500)));
+        assert_eq!(config.read_timeout, Some(Duration::from_secs(30)));
+        assert_eq!(config.max_attempts, Some(5));
+        let oss_instance = config.build().unwrap();
+        assert_eq!(
+            oss_instance.region,
+            Region::custom("cn-private-1", "oss.example.com:8080")
+        );
+        assert_eq!(oss_instance.get_bucket(), "staging-bucket");
+        assert_eq!(oss_instance.get_retry_policy().max_attempts, 5);
+        assert_eq!(oss_instance.get_timeouts().request, None);
+        assert!(!format!("{:?}", config).contains("sts-token"));
+
+        assert!(OssConfig::from_file_profile(&path, Some("prod")).is_err());
+        fs::write(&path, "[default]\nconnect_timeout = soon\n").unwrap();
+        assert!(OssConfig::from_file_profile(&path, None).is_err());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(feature = "serde-config")]
+    #[test]
+    fn serde_test() {
+        let path = write(
+            "config.toml",
+            "[default]\nregion = \"cn-beijing\"\nbucket = \"bucket\"\nrequest_timeout = 120\n\
+             [other]\nendpoint = \"oss-cn-hangzhou.aliyuncs.com\"\nmax_attempts = 1\n",
+        );
+        let config = OssConfig::from_file_profile(&path, None).unwrap();
+        assert_eq!(config.request_timeout, Some(Duration::from_secs(120)));
+        assert_eq!(config.build().unwrap().region, Region::BeiJing);
+        let config = OssConfig::from_file_profile(&path, Some("other")).unwrap();
+        assert_eq!(config.build().unwrap().region, Region::HangZhou);
+        fs::remove_file(path).unwrap();
+
+        let path = write(
+            "config.json",
+            r#"{"default": {"region": "cn-beijing", "retry_deadline": 0.5}}"#,
+        );
+        let config = OssConfig::from_file(&path).unwrap();
+        assert_eq!(config.retry_deadline, Some(Duration::from_millis(500)));
+        fs::remove_file(path).unwrap();
+
+        // Keys are normalized as for the INI files
+        let path = write(
+            "camel.toml",
+            "[default]\nregion = \"cn-beijing\"\naccessKeyID = \"id\"\n\
+             access-key-secret = \"secret\"\nstsToken = \"sts-token\"\nretry-times = 2\n",
+        );
+        let config = OssConfig::from_file_profile(&path, None).unwrap();
+        assert_eq!(config.access_key_id.as_deref(), Some("id"));
+        assert_eq!(config.access_key_secret.as_deref(), Some("secret"));
+        assert_eq!(config.security_token.as_deref(), Some("sts-token"));
+        assert_eq!(config.max_attempts, Some(3));
+        fs::write(&path, "[default]\nrequestTimeout = \"soon\"\n").unwrap();
+        assert!(OssConfig::from_file_profile(&path, None).is_err());
+        fs::remove_file(path).unwrap();
+    }
+}
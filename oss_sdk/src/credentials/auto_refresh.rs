@@ -0,0 +1,224 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use async_trait::async_trait;
+use chrono::Duration;
+use tokio::sync::Mutex;
+
+use super::{Credentials, CredentialsError, CredentialsProvider};
+
+/// Credentials this close to their expiry are not handed out anymore.
+const EXPIRY_MARGIN_SECS: i64 = 10;
+
+/// The credentials of the last fetch, shared with the providers that cache
+/// their own.
+#[derive(Clone, Default)]
+pub(super) struct Cache {
+    cached: Arc<RwLock<Option<Credentials>>>,
+    fetch_lock: Arc<Mutex<()>>,
+}
+
+impl Cache {
+    /// The cached credentials, unless they are about to expire.
+    pub(super) fn get(&self) -> Option<Credentials> {
+        self.cached
+            .read()
+            .unwrap()
+            .as_ref()
+            .filter(|credentials| {
+                !credentials.expires_within(Duration::seconds(EXPIRY_MARGIN_SECS))
+            })
+            .cloned()
+    }
+
+    /// The cached credentials, or new ones from `fetch` once they are about
+    /// to expire. Concurrent callers wait for a single `fetch`.
+    pub(super) async fn get_or_fetch<F, Fut>(
+        &self,
+        fetch: F,
+    ) -> Result<Credentials, CredentialsError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Credentials, CredentialsError>>,
+    {
+        if let Some(credentials) = self.get() {
+            return Ok(credentials);
+        }
+        let _guard = self.fetch_lock.lock().await;
+        // Someone else may have fetched them while we were waiting.
+        if let Some(credentials) = self.get() {
+            return Ok(credentials);
+        }
+        let credentials = fetch().await?;
+        *self.cached.write().unwrap() = Some(credentials.clone());
+        Ok(credentials)
+    }
+
+    /// Replaces the cached credentials with new ones from `fetch`, even if
+    /// they are still valid.
+    async fn refresh<F, Fut>(&self, fetch: F) -> Result<(), CredentialsError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Credentials, CredentialsError>>,
+    {
+        let _guard = self.fetch_lock.lock().await;
+        let credentials = fetch().await?;
+        *self.cached.write().unwrap() = Some(credentials);
+        Ok(())
+    }
+}
+
+/// Caches the credentials of another provider until they are about to expire.
+///
+/// Within the refresh window (5 minutes by default) before the expiry the cached
+/// credentials are still handed out while new ones are fetched in the background;
+/// expired ones are refreshed before returning. Credentials without an expiry
+/// are cached for good.
+pub struct AutoRefreshingProvider<P> {
+    inner: Arc<P>,
+    cache: Cache,
+    refreshing: Arc<AtomicBool>,
+    refresh_window: Duration,
+}
+
+impl<P: CredentialsProvider + 'static> AutoRefreshingProvider<P> {
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            cache: Cache::default(),
+            refreshing: Arc::new(AtomicBool::new(false)),
+            refresh_window: Duration::minutes(5),
+        }
+    }
+    /// How long before their expiry credentials are refreshed in the background.
+    pub fn refresh_window(mut self, refresh_window: std::time::Duration) -> Self {
+        self.refresh_window = Duration::from_std(refresh_window).unwrap_or(self.refresh_window);
+        self
+    }
+
+    fn spawn_refresh(&self) {
+        let handle = match tokio::runtime::Handle::try_current() {
+            Ok(handle) => handle,
+            Err(_) => return,
+        };
+        if self.refreshing.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let inner = self.inner.clone();
+        let cache = self.cache.clone();
+        let refreshing = self.refreshing.clone();
+        handle.spawn(async move {
+            if let Err(e) = cache.refresh(|| inner.credentials()).await {
+                log::warn!("failed to refresh credentials in the background: {}", e);
+            }
+            refreshing.store(false, Ordering::SeqCst);
+        });
+    }
+}
+
+#[async_trait]
+impl<P: CredentialsProvider + 'static> CredentialsProvider for AutoRefreshingProvider<P> {
+    async fn credentials(&self) -> Result<Credentials, CredentialsError> {
+        if let Some(credentials) = self.cache.get() {
+            if credentials.expires_within(self.refresh_window) {
+                self.spawn_refresh();
+            }
+            return Ok(credentials);
+        }
+        self.cache.get_or_fetch(|| self.inner.credentials()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::sync::atomic::AtomicUsize;
+
+    struct CountingProvider {
+        calls: Arc<AtomicUsize>,
+        lifetime: Duration,
+    }
+
+    #[async_trait]
+    impl CredentialsProvider for CountingProvider {
+        async fn credentials(&self) -> Result<Credentials, CredentialsError> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(Credentials::new_with_token(
+                format!("ak-{}", call),
+                "sk",
+                "token",
+                Utc::now() + self.lifetime,
+            ))
+        }
+    }
+
+    fn provider(
+        lifetime: Duration,
+    ) -> (AutoRefreshingProvider<CountingProvider>, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = AutoRefreshingProvider::new(CountingProvider {
+            calls: calls.clone(),
+            lifetime,
+        });
+        (provider, calls)
+    }
+
+    #[tokio::test]
+    async fn caches_until_refresh_window_test() {
+        let (provider, calls) = provider(Duration::hours(1));
+        assert_eq!(
+            provider.credentials().await.unwrap().access_key_id(),
+            "ak-1"
+        );
+        assert_eq!(
+            provider.credentials().await.unwrap().access_key_id(),
+            "ak-1"
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn refreshes_in_background_test() {
+        let (provider, calls) = provider(Duration::minutes(2));
+        assert_eq!(
+            provider.credentials().await.unwrap().access_key_id(),
+            "ak-1"
+        );
+        // Within the refresh window: still the cached ones, new ones are fetched meanwhile.
+        assert_eq!(
+            provider.credentials().await.unwrap().access_key_id(),
+            "ak-1"
+        );
+        for _ in 0..100 {
+            if calls.load(Ordering::SeqCst) == 2 {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            provider.credentials().await.unwrap().access_key_id(),
+            "ak-2"
+        );
+    }
+
+    #[tokio::test]
+    async fn refreshes_expired_test() {
+        let (provider, calls) = provider(Duration::seconds(1));
+        assert_eq!(
+            provider.credentials().await.unwrap().access_key_id(),
+            "ak-1"
+        );
+        assert_eq!(
+            provider.credentials().await.unwrap().access_key_id(),
+            "ak-2"
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
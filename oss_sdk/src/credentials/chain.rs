@@ -0,0 +1,158 @@
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+
+use super::{
+    AutoRefreshingProvider, Credentials, CredentialsError, CredentialsProvider, EcsRamRoleProvider,
+    EnvironmentProvider, OidcRoleProvider, ProfileProvider,
+};
+
+/// Tries its providers in order and hands out the credentials of the first one
+/// that succeeds. That provider is asked alone afterwards, the chain is only
+/// walked again once it fails.
+pub struct ChainProvider {
+    providers: Vec<Box<dyn CredentialsProvider>>,
+    /// Index of the provider that answered last.
+    answered: RwLock<Option<usize>>,
+}
+
+impl ChainProvider {
+    pub fn new(providers: Vec<Box<dyn CredentialsProvider>>) -> Self {
+        Self {
+            providers,
+            answered: RwLock::new(None),
+        }
+    }
+    pub fn push<P: CredentialsProvider + 'static>(&mut self, provider: P) {
+        self.providers.push(Box::new(provider));
+    }
+}
+
+/// Environment variables, then the ossutil / aliyun CLI config files, then
+/// the RRSA OIDC role when its environment variables are set, then the RAM
+/// role of the ECS instance.
+impl Default for ChainProvider {
+    fn default() -> Self {
+        let mut chain = Self::new(vec![
+            Box::new(EnvironmentProvider::new()),
+            Box::new(ProfileProvider::new()),
+        ]);
+        if let Some(oidc) = OidcRoleProvider::from_env() {
+            chain.push(oidc);
+        }
+        chain.push(AutoRefreshingProvider::new(EcsRamRoleProvider::new()));
+        chain
+    }
+}
+
+#[async_trait]
+impl CredentialsProvider for ChainProvider {
+    async fn credentials(&self) -> Result<Credentials, CredentialsError> {
+        let answered = *self.answered.read().unwrap();
+        let mut errors = Vec::new();
+        if let Some(index) = answered {
+            match self.providers[index].credentials().await {
+                Ok(credentials) => return Ok(credentials),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        for (index, provider) in self.providers.iter().enumerate() {
+            if Some(index) == answered {
+                continue;
+            }
+            match provider.credentials().await {
+                Ok(credentials) => {
+                    *self.answered.write().unwrap() = Some(index);
+                    return Ok(credentials);
+                }
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        *self.answered.write().unwrap() = None;
+        Err(CredentialsError::new(format!(
+            "no provider in the chain could provide credentials: [{}]",
+            errors.join("; ")
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+    use crate::credentials::StaticProvider;
+
+    struct FailingProvider;
+
+    #[async_trait]
+    impl CredentialsProvider for FailingProvider {
+        async fn credentials(&self) -> Result<Credentials, CredentialsError> {
+            Err(CredentialsError::new("nothing here"))
+        }
+    }
+
+    /// Counts its calls, and answers as `name` while `up`.
+    struct CountingProvider {
+        name: &'static str,
+        up: Arc<AtomicBool>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl CountingProvider {
+        fn new(name: &'static str, up: bool) -> (Self, Arc<AtomicBool>, Arc<AtomicUsize>) {
+            let provider = Self {
+                name,
+                up: Arc::new(AtomicBool::new(up)),
+                calls: Arc::new(AtomicUsize::new(0)),
+            };
+            let (up, calls) = (provider.up.clone(), provider.calls.clone());
+            (provider, up, calls)
+        }
+    }
+
+    #[async_trait]
+    impl CredentialsProvider for CountingProvider {
+        async fn credentials(&self) -> Result<Credentials, CredentialsError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            match self.up.load(Ordering::SeqCst) {
+                true => Ok(Credentials::new(self.name, "sk")),
+                false => Err(CredentialsError::new(format!("{} is down", self.name))),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn chain_test() {
+        let mut chain = ChainProvider::new(vec![Box::new(FailingProvider)]);
+        let err = chain.credentials().await.unwrap_err();
+        assert!(err.to_string().contains("nothing here"));
+
+        chain.push(StaticProvider::new("ak", "sk"));
+        chain.push(StaticProvider::new("other", "sk"));
+        assert_eq!(chain.credentials().await.unwrap().access_key_id(), "ak");
+    }
+
+    #[tokio::test]
+    async fn remembers_provider_test() {
+        let (first, first_up, first_calls) = CountingProvider::new("first", false);
+        let (second, second_up, second_calls) = CountingProvider::new("second", true);
+        let chain = ChainProvider::new(vec![Box::new(first), Box::new(second)]);
+        for _ in 0..3 {
+            assert_eq!(chain.credentials().await.unwrap().access_key_id(), "second");
+        }
+        assert_eq!(first_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(second_calls.load(Ordering::SeqCst), 3);
+
+        // The chain is walked again once the remembered provider fails
+        first_up.store(true, Ordering::SeqCst);
+        second_up.store(false, Ordering::SeqCst);
+        assert_eq!(chain.credentials().await.unwrap().access_key_id(), "first");
+        assert_eq!(chain.credentials().await.unwrap().access_key_id(), "first");
+        assert_eq!(first_calls.load(Ordering::SeqCst), 3);
+        assert_eq!(second_calls.load(Ordering::SeqCst), 4);
+    }
+}
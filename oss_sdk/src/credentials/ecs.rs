@@ -0,0 +1,160 @@
+use std::{env, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::{Credentials, CredentialsError, CredentialsProvider};
+use crate::statics::reqwest_client;
+
+pub const ECS_METADATA_ENDPOINT: &str =
+    "http://100.100.100.200/latest/meta-data/ram/security-credentials/";
+
+/// Temporary credentials of the RAM role attached to the ECS instance, served
+/// by the instance metadata service. They expire, so wrap the provider in an
+/// `AutoRefreshingProvider`.
+#[derive(Clone, Debug)]
+pub struct EcsRamRoleProvider {
+    endpoint: String,
+    role_name: Option<String>,
+    timeout: Duration,
+}
+
+impl Default for EcsRamRoleProvider {
+    fn default() -> Self {
+        Self {
+            endpoint: ECS_METADATA_ENDPOINT.to_owned(),
+            role_name: env::var("ALIBABA_CLOUD_ECS_METADATA")
+                .ok()
+                .filter(|role| !role.is_empty()),
+            timeout: Duration::from_secs(1),
+        }
+    }
+}
+
+impl EcsRamRoleProvider {
+    /// The role named by `ALIBABA_CLOUD_ECS_METADATA`, otherwise the one the
+    /// metadata service lists.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Url of the `security-credentials/` directory of the metadata service.
+    pub fn endpoint<S: Into<String>>(mut self, endpoint: S) -> Self {
+        self.endpoint = endpoint.into();
+        if !self.endpoint.ends_with('/') {
+            self.endpoint.push('/');
+        }
+        self
+    }
+    pub fn role_name<S: Into<String>>(mut self, role_name: S) -> Self {
+        self.role_name = Some(role_name.into());
+        self
+    }
+    /// Timeout of each call to the metadata service, 1 second by default so
+    /// that a chain quickly moves on outside of ECS.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn get(&self, url: &str) -> Result<String, CredentialsError> {
+        let fail = |e| CredentialsError::with_source("failed to query ECS instance metadata", e);
+        let resp = reqwest_client()
+            .get(url)
+            .timeout(self.timeout)
+            .send()
+            .await
+            .map_err(fail)?;
+        if !resp.status().is_success() {
+            return Err(CredentialsError::new(format!(
+                "ECS instance metadata responded {} for {}",
+                resp.status(),
+                url
+            )));
+        }
+        resp.text().await.map_err(fail)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct EcsCredentials {
+    code: String,
+    access_key_id: String,
+    access_key_secret: String,
+    security_token: String,
+    expiration: String,
+}
+
+#[async_trait]
+impl CredentialsProvider for EcsRamRoleProvider {
+    async fn credentials(&self) -> Result<Credentials, CredentialsError> {
+        let role_name = match self.role_name {
+            Some(ref role_name) => role_name.clone(),
+            None => self
+                .get(&self.endpoint)
+                .await?
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .ok_or_else(|| CredentialsError::new("no RAM role attached to the instance"))?
+                .to_owned(),
+        };
+        let body = self.get(&format!("{}{}", self.endpoint, role_name)).await?;
+        let ecs: EcsCredentials = serde_json::from_str(&body)
+            .map_err(|e| CredentialsError::with_source("invalid ECS RAM role credentials", e))?;
+        if ecs.code != "Success" {
+            return Err(CredentialsError::new(format!(
+                "ECS RAM role credentials returned code {}",
+                ecs.code
+            )));
+        }
+        let expiration = ecs.expiration.parse::<DateTime<Utc>>().map_err(|e| {
+            CredentialsError::with_source("invalid ECS RAM role credentials expiration", e)
+        })?;
+        Ok(Credentials::new_with_token(
+            ecs.access_key_id,
+            ecs.access_key_secret,
+            ecs.security_token,
+            expiration,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{MockResponse, MockServer};
+
+    #[tokio::test]
+    async fn ecs_ram_role_test() {
+        let server = MockServer::start(|rqst| match rqst.path.as_str() {
+            "/latest/meta-data/ram/security-credentials/" => MockResponse::ok("EcsRole\n"),
+            "/latest/meta-data/ram/security-credentials/EcsRole" => MockResponse::ok(
+                r#"{
+                    "AccessKeyId": "STS.ak",
+                    "AccessKeySecret": "sk",
+                    "Expiration": "2030-11-01T05:20:01Z",
+                    "SecurityToken": "token",
+                    "LastUpdated": "2030-10-31T23:20:01Z",
+                    "Code": "Success"
+                }"#,
+            ),
+            _ => MockResponse::status(404),
+        })
+        .await;
+        let provider = EcsRamRoleProvider::new()
+            .endpoint(server.url("/latest/meta-data/ram/security-credentials"));
+        let credentials = provider.credentials().await.unwrap();
+        assert_eq!(credentials.access_key_id(), "STS.ak");
+        assert_eq!(credentials.access_key_secret(), "sk");
+        assert_eq!(credentials.security_token(), Some("token"));
+        assert_eq!(
+            credentials.expiration(),
+            Some("2030-11-01T05:20:01Z".parse().unwrap())
+        );
+
+        let provider = provider.role_name("Missing");
+        assert!(provider.credentials().await.is_err());
+    }
+}
@@ -0,0 +1,45 @@
+use std::env;
+
+use async_trait::async_trait;
+
+use super::{Credentials, CredentialsError, CredentialsProvider};
+
+/// Reads `OSS_ACCESS_KEY_ID`, `OSS_ACCESS_KEY_SECRET` and the optional
+/// `OSS_SESSION_TOKEN`, falling back to their `ALIBABA_CLOUD_ACCESS_KEY_ID`,
+/// `ALIBABA_CLOUD_ACCESS_KEY_SECRET` and `ALIBABA_CLOUD_SECURITY_TOKEN` counterparts.
+/// The environment is read on every call.
+#[derive(Clone, Debug, Default)]
+pub struct EnvironmentProvider {
+    _priv: (),
+}
+
+impl EnvironmentProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CredentialsProvider for EnvironmentProvider {
+    async fn credentials(&self) -> Result<Credentials, CredentialsError> {
+        let access_key_id = var("OSS_ACCESS_KEY_ID", "ALIBABA_CLOUD_ACCESS_KEY_ID")
+            .ok_or_else(|| CredentialsError::new("OSS_ACCESS_KEY_ID is not set"))?;
+        let access_key_secret = var("OSS_ACCESS_KEY_SECRET", "ALIBABA_CLOUD_ACCESS_KEY_SECRET")
+            .ok_or_else(|| CredentialsError::new("OSS_ACCESS_KEY_SECRET is not set"))?;
+        Ok(
+            match var("OSS_SESSION_TOKEN", "ALIBABA_CLOUD_SECURITY_TOKEN") {
+                Some(token) => {
+                    Credentials::new_with_token(access_key_id, access_key_secret, token, None)
+                }
+                None => Credentials::new(access_key_id, access_key_secret),
+            },
+        )
+    }
+}
+
+fn var(name: &str, fallback: &str) -> Option<String> {
+    env::var(name)
+        .or_else(|_| env::var(fallback))
+        .ok()
+        .filter(|val| !val.is_empty())
+}
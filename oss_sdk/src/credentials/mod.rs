@@ -0,0 +1,193 @@
+use std::{error::Error as StdError, fmt, sync::Arc};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+
+mod auto_refresh;
+mod chain;
+mod ecs;
+mod environment;
+pub(crate) mod profile;
+mod sts;
+
+pub use auto_refresh::AutoRefreshingProvider;
+pub use chain::ChainProvider;
+pub use ecs::EcsRamRoleProvider;
+pub use environment::EnvironmentProvider;
+pub use profile::ProfileProvider;
+pub use sts::{AssumeRoleProvider, OidcRoleProvider, STS_ENDPOINT};
+
+type BoxedError = Box<dyn StdError + Send + Sync>;
+
+/// Source of the credentials requests are signed with. It is asked again
+/// right before every request is signed, so it may hand out rotated keys.
+#[async_trait]
+pub trait CredentialsProvider: Send + Sync {
+    async fn credentials(&self) -> Result<Credentials, CredentialsError>;
+}
+
+#[async_trait]
+impl<P: CredentialsProvider + ?Sized> CredentialsProvider for Arc<P> {
+    async fn credentials(&self) -> Result<Credentials, CredentialsError> {
+        (**self).credentials().await
+    }
+}
+
+#[async_trait]
+impl<P: CredentialsProvider + ?Sized> CredentialsProvider for Box<P> {
+    async fn credentials(&self) -> Result<Credentials, CredentialsError> {
+        (**self).credentials().await
+    }
+}
+
+/// Always hands out the same credentials.
+#[derive(Clone, Debug)]
+pub struct StaticProvider {
+    credentials: Credentials,
+}
+
+impl StaticProvider {
+    pub fn new<S1, S2>(access_key_id: S1, access_key_secret: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        Self {
+            credentials: Credentials::new(access_key_id, access_key_secret),
+        }
+    }
+}
+
+impl From<Credentials> for StaticProvider {
+    fn from(credentials: Credentials) -> Self {
+        Self { credentials }
+    }
+}
+
+#[async_trait]
+impl CredentialsProvider for StaticProvider {
+    async fn credentials(&self) -> Result<Credentials, CredentialsError> {
+        Ok(self.credentials.clone())
+    }
+}
+
+/// Failure to obtain credentials from a `CredentialsProvider`.
+#[derive(Debug)]
+pub struct CredentialsError {
+    message: String,
+    source: Option<BoxedError>,
+}
+
+impl CredentialsError {
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        CredentialsError {
+            message: message.into(),
+            source: None,
+        }
+    }
+    pub(crate) fn with_source<S, E>(message: S, source: E) -> Self
+    where
+        S: Into<String>,
+        E: Into<BoxedError>,
+    {
+        CredentialsError {
+            message: message.into(),
+            source: Some(source.into()),
+        }
+    }
+}
+
+impl fmt::Display for CredentialsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)?;
+        if let Some(ref e) = self.source {
+            write!(f, ": {}", e)?;
+        }
+        Ok(())
+    }
+}
+
+impl StdError for CredentialsError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.source.as_ref().map(|e| &**e as _)
+    }
+}
+
+/// An access key pair, plus the security token and expiry of temporary STS credentials.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Credentials {
+    access_key_id: String,
+    access_key_secret: String,
+    security_token: Option<String>,
+    expiration: Option<DateTime<Utc>>,
+}
+
+impl Credentials {
+    /// Long term credentials, which never expire.
+    pub fn new<S1, S2>(access_key_id: S1, access_key_secret: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        Self {
+            access_key_id: access_key_id.into(),
+            access_key_secret: access_key_secret.into(),
+            ..Default::default()
+        }
+    }
+    /// Temporary credentials issued by STS.
+    pub fn new_with_token<S1, S2, S3, E>(
+        access_key_id: S1,
+        access_key_secret: S2,
+        security_token: S3,
+        expiration: E,
+    ) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<String>,
+        E: Into<Option<DateTime<Utc>>>,
+    {
+        Self {
+            access_key_id: access_key_id.into(),
+            access_key_secret: access_key_secret.into(),
+            security_token: Some(security_token.into()).filter(|token| !token.is_empty()),
+            expiration: expiration.into(),
+        }
+    }
+
+    pub fn access_key_id(&self) -> &str {
+        &self.access_key_id
+    }
+    pub fn access_key_secret(&self) -> &str {
+        &self.access_key_secret
+    }
+    pub fn security_token(&self) -> Option<&str> {
+        self.security_token.as_deref()
+    }
+    pub fn expiration(&self) -> Option<DateTime<Utc>> {
+        self.expiration
+    }
+    /// Whether the credentials expire within `window` from now.
+    pub fn expires_within(&self, window: Duration) -> bool {
+        self.expiration
+            .is_some_and(|expiration| expiration - window <= Utc::now())
+    }
+    pub fn is_expired(&self) -> bool {
+        self.expires_within(Duration::zero())
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("access_key_secret", &"** redacted **")
+            .field(
+                "security_token",
+                &self.security_token.as_ref().map(|_| "** redacted **"),
+            )
+            .field("expiration", &self.expiration)
+            .finish()
+    }
+}
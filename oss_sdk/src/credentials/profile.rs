@@ -0,0 +1,286 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::{Credentials, CredentialsError, CredentialsProvider};
+
+/// Reads the credentials of a profile from an ossutil config file
+/// (`~/.ossutilconfig`, INI) or an aliyun CLI config file (`~/.aliyun/config.json`).
+///
+/// In an ossutil file the `default` profile is the `[Credentials]` (ossutil 1.x)
+/// or `[default]` section, any other profile `[name]` or `[profile name]`.
+/// In an aliyun CLI file the default profile is the `current` one.
+#[derive(Clone, Debug)]
+pub struct ProfileProvider {
+    paths: Vec<PathBuf>,
+    profile: Option<String>,
+}
+
+impl Default for ProfileProvider {
+    fn default() -> Self {
+        let paths = home_dir()
+            .map(|home| {
+                vec![
+                    home.join(".ossutilconfig"),
+                    home.join(".aliyun").join("config.json"),
+                ]
+            })
+            .unwrap_or_default();
+        Self {
+            paths,
+            profile: env::var("OSS_PROFILE").ok(),
+        }
+    }
+}
+
+impl ProfileProvider {
+    /// `~/.ossutilconfig` then `~/.aliyun/config.json`, the profile named by
+    /// `OSS_PROFILE` if set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// A single config file, JSON files are read as aliyun CLI configs.
+    pub fn with_path<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            paths: vec![path.into()],
+            profile: None,
+        }
+    }
+    pub fn profile<S: Into<String>>(mut self, profile: S) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    async fn read(&self, path: &Path) -> Result<Credentials, CredentialsError> {
+        let content = tokio::fs::read_to_string(path).await.map_err(|e| {
+            CredentialsError::with_source(format!("failed to read {}", path.display()), e)
+        })?;
+        let profile = self.profile.as_deref();
+        let credentials = if content.trim_start().starts_with('{') {
+            from_aliyun_cli_config(&content, profile)?
+        } else {
+            from_ossutil_config(&content, profile)
+        };
+        credentials.ok_or_else(|| {
+            CredentialsError::new(format!(
+                "no credentials for profile {} in {}",
+                profile.unwrap_or("default"),
+                path.display()
+            ))
+        })
+    }
+}
+
+#[async_trait]
+impl CredentialsProvider for ProfileProvider {
+    async fn credentials(&self) -> Result<Credentials, CredentialsError> {
+        let mut errors = Vec::new();
+        for path in self.paths.iter() {
+            match self.read(path).await {
+                Ok(credentials) => return Ok(credentials),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        Err(CredentialsError::new(format!(
+            "no usable config file: [{}]",
+            errors.join("; ")
+        )))
+    }
+}
+
+pub(crate) fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Sections of an INI file with their `key=value` pairs. Keys are lowercased,
+/// `#` and `;` start comment lines.
+pub(crate) fn parse_ini(content: &str) -> Vec<(String, Vec<(String, String)>)> {
+    let mut sections: Vec<(String, Vec<(String, String)>)> = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.push((name.trim().to_owned(), Vec::new()));
+        } else if let (Some((k, v)), Some((_, entries))) =
+            (line.split_once('='), sections.last_mut())
+        {
+            entries.push((k.trim().to_ascii_lowercase(), v.trim().to_owned()));
+        }
+    }
+    sections
+}
+
+/// The section of `profile` in an ossutil style INI file.
+pub(crate) fn ini_profile<'a>(
+    sections: &'a [(String, Vec<(String, String)>)],
+    profile: Option<&str>,
+) -> Option<&'a [(String, String)]> {
+    let matches = |name: &str| match profile {
+        None | Some("default") => {
+            name.eq_ignore_ascii_case("credentials") || name.eq_ignore_ascii_case("default")
+        }
+        Some(profile) => {
+            name == profile || name.strip_prefix("profile ").map(str::trim) == Some(profile)
+        }
+    };
+    sections
+        .iter()
+        .find(|(name, _)| matches(name))
+        .map(|(_, entries)| entries.as_slice())
+}
+
+fn from_ossutil_config(content: &str, profile: Option<&str>) -> Option<Credentials> {
+    let sections = parse_ini(content);
+    let entries = ini_profile(&sections, profile)?;
+    let get = |keys: &[&str]| {
+        entries
+            .iter()
+            .find(|(k, v)| keys.contains(&k.as_str()) && !v.is_empty())
+            .map(|(_, v)| v.clone())
+    };
+    let access_key_id = get(&["accesskeyid", "access_key_id"])?;
+    let access_key_secret = get(&["accesskeysecret", "access_key_secret"])?;
+    Some(
+        match get(&["ststoken", "securitytoken", "sts_token", "security_token"]) {
+            Some(token) => {
+                Credentials::new_with_token(access_key_id, access_key_secret, token, None)
+            }
+            None => Credentials::new(access_key_id, access_key_secret),
+        },
+    )
+}
+
+#[derive(Deserialize)]
+struct AliyunCliConfig {
+    #[serde(default)]
+    current: Option<String>,
+    #[serde(default)]
+    profiles: Vec<AliyunCliProfile>,
+}
+
+#[derive(Deserialize)]
+struct AliyunCliProfile {
+    name: String,
+    #[serde(default)]
+    access_key_id: String,
+    #[serde(default)]
+    access_key_secret: String,
+    #[serde(default)]
+    sts_token: String,
+}
+
+fn from_aliyun_cli_config(
+    content: &str,
+    profile: Option<&str>,
+) -> Result<Option<Credentials>, CredentialsError> {
+    let config: AliyunCliConfig = serde_json::from_str(content)
+        .map_err(|e| CredentialsError::with_source("invalid aliyun CLI config", e))?;
+    let name = profile
+        .map(str::to_owned)
+        .or(config.current)
+        .unwrap_or_else(|| "default".to_owned());
+    Ok(config
+        .profiles
+        .into_iter()
+        .find(|p| p.name == name)
+        .filter(|p| !p.access_key_id.is_empty() && !p.access_key_secret.is_empty())
+        .map(|p| {
+            if p.sts_token.is_empty() {
+                Credentials::new(p.access_key_id, p.access_key_secret)
+            } else {
+                Credentials::new_with_token(p.access_key_id, p.access_key_secret, p.sts_token, None)
+            }
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OSSUTIL_CONFIG: &str = "
+[Credentials]
+language=EN
+endpoint=oss-cn-hangzhou.aliyuncs.com
+accessKeyID=default-id
+accessKeySecret=default-secret
+
+# named profile of ossutil 2.x
+[profile dev]
+accessKeyId = dev-id
+accessKeySecret = dev-secret
+stsToken = dev-token
+";
+
+    const ALIYUN_CLI_CONFIG: &str = r#"{
+        "current": "sts",
+        "profiles": [
+            {"name": "default", "mode": "AK", "access_key_id": "default-id", "access_key_secret": "default-secret"},
+            {"name": "sts", "mode": "StsToken", "access_key_id": "sts-id", "access_key_secret": "sts-secret", "sts_token": "sts-token"}
+        ]
+    }"#;
+
+    #[tokio::test]
+    async fn profile_test() {
+        let dir = crate::test_util::temp_path("profile");
+        std::fs::create_dir_all(&dir).unwrap();
+        let ossutil = dir.join("ossutilconfig");
+        std::fs::write(&ossutil, OSSUTIL_CONFIG).unwrap();
+        let aliyun = dir.join("config.json");
+        std::fs::write(&aliyun, ALIYUN_CLI_CONFIG).unwrap();
+
+        let credentials = ProfileProvider::with_path(&ossutil)
+            .credentials()
+            .await
+            .unwrap();
+        assert_eq!(
+            credentials,
+            Credentials::new("default-id", "default-secret")
+        );
+        let credentials = ProfileProvider::with_path(&ossutil)
+            .profile("dev")
+            .credentials()
+            .await
+            .unwrap();
+        assert_eq!(
+            credentials,
+            Credentials::new_with_token("dev-id", "dev-secret", "dev-token", None)
+        );
+        assert!(ProfileProvider::with_path(&ossutil)
+            .profile("prod")
+            .credentials()
+            .await
+            .is_err());
+
+        let credentials = ProfileProvider::with_path(&aliyun)
+            .credentials()
+            .await
+            .unwrap();
+        assert_eq!(
+            credentials,
+            Credentials::new_with_token("sts-id", "sts-secret", "sts-token", None)
+        );
+        let credentials = ProfileProvider::with_path(&aliyun)
+            .profile("default")
+            .credentials()
+            .await
+            .unwrap();
+        assert_eq!(
+            credentials,
+            Credentials::new("default-id", "default-secret")
+        );
+
+        assert!(ProfileProvider::with_path(dir.join("missing"))
+            .credentials()
+            .await
+            .is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
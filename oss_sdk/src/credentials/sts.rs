@@ -0,0 +1,431 @@
+use std::{
+    collections::{hash_map::RandomState, BTreeMap},
+    env,
+    hash::{BuildHasher, Hasher},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use base64::encode;
+use chrono::{DateTime, Utc};
+use crypto::{hmac::Hmac, mac::Mac, sha1::Sha1};
+use serde::Deserialize;
+
+use super::{auto_refresh::Cache, Credentials, CredentialsError, CredentialsProvider};
+use crate::{http_client::encoding::encode_query, statics::reqwest_client};
+
+pub const STS_ENDPOINT: &str = "https://sts.aliyuncs.com";
+const STS_VERSION: &str = "2015-04-01";
+const DEFAULT_SESSION_NAME: &str = "rust-oss-sdk";
+
+/// Temporary credentials of a RAM role, assumed by calling the STS `AssumeRole`
+/// API with the credentials of `source`. They are cached until shortly before
+/// they expire.
+pub struct AssumeRoleProvider {
+    source: Arc<dyn CredentialsProvider>,
+    role_arn: String,
+    options: StsOptions,
+    external_id: Option<String>,
+    cache: Cache,
+}
+
+impl AssumeRoleProvider {
+    pub fn new<P, S>(source: P, role_arn: S) -> Self
+    where
+        P: CredentialsProvider + 'static,
+        S: Into<String>,
+    {
+        Self {
+            source: Arc::new(source),
+            role_arn: role_arn.into(),
+            options: StsOptions::default(),
+            external_id: None,
+            cache: Cache::default(),
+        }
+    }
+    /// Url of the STS service, `https://sts.aliyuncs.com` by default.
+    pub fn endpoint<S: Into<String>>(mut self, endpoint: S) -> Self {
+        self.options.endpoint = endpoint.into();
+        self
+    }
+    pub fn role_session_name<S: Into<String>>(mut self, role_session_name: S) -> Self {
+        self.options.role_session_name = role_session_name.into();
+        self
+    }
+    /// Validity of the assumed credentials, 1 hour by default.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.options.duration = duration;
+        self
+    }
+    /// A policy further restricting the permissions of the assumed role.
+    pub fn policy<S: Into<String>>(mut self, policy: S) -> Self {
+        self.options.policy = Some(policy.into());
+        self
+    }
+    pub fn external_id<S: Into<String>>(mut self, external_id: S) -> Self {
+        self.external_id = Some(external_id.into());
+        self
+    }
+}
+
+#[async_trait]
+impl CredentialsProvider for AssumeRoleProvider {
+    async fn credentials(&self) -> Result<Credentials, CredentialsError> {
+        self.cache.get_or_fetch(|| self.assume_role()).await
+    }
+}
+
+impl AssumeRoleProvider {
+    async fn assume_role(&self) -> Result<Credentials, CredentialsError> {
+        let source = self.source.credentials().await?;
+        let mut params = self.options.params("AssumeRole", &self.role_arn);
+        if let Some(ref external_id) = self.external_id {
+            params.insert("ExternalId", external_id.clone());
+        }
+        params.insert("AccessKeyId", source.access_key_id().to_owned());
+        if let Some(token) = source.security_token() {
+            params.insert("SecurityToken", token.to_owned());
+        }
+        params.insert("SignatureMethod", "HMAC-SHA1".to_owned());
+        params.insert("SignatureVersion", "1.0".to_owned());
+        params.insert("SignatureNonce", nonce());
+        let signature = sign_rpc("POST", &params, source.access_key_secret());
+        params.insert("Signature", signature);
+        call(&self.options.endpoint, &params).await
+    }
+}
+
+/// Temporary credentials of a RAM role, assumed by calling the STS
+/// `AssumeRoleWithOIDC` API with the OIDC token in `oidc_token_file`, as done
+/// by RRSA on ACK (Kubernetes). They are cached until shortly before they
+/// expire, and the token file is read anew on every STS call since it gets
+/// rotated.
+pub struct OidcRoleProvider {
+    role_arn: String,
+    oidc_provider_arn: String,
+    oidc_token_file: PathBuf,
+    options: StsOptions,
+    cache: Cache,
+}
+
+impl OidcRoleProvider {
+    pub fn new<S1, S2, P>(role_arn: S1, oidc_provider_arn: S2, oidc_token_file: P) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        P: Into<PathBuf>,
+    {
+        Self {
+            role_arn: role_arn.into(),
+            oidc_provider_arn: oidc_provider_arn.into(),
+            oidc_token_file: oidc_token_file.into(),
+            options: StsOptions::default(),
+            cache: Cache::default(),
+        }
+    }
+    /// Configured by `ALIBABA_CLOUD_ROLE_ARN`, `ALIBABA_CLOUD_OIDC_PROVIDER_ARN`
+    /// and `ALIBABA_CLOUD_OIDC_TOKEN_FILE`, which RRSA injects into the pod,
+    /// plus the optional `ALIBABA_CLOUD_ROLE_SESSION_NAME`. `None` if any of
+    /// the former is unset.
+    pub fn from_env() -> Option<Self> {
+        let var = |name| env::var(name).ok().filter(|v| !v.is_empty());
+        let mut provider = Self::new(
+            var("ALIBABA_CLOUD_ROLE_ARN")?,
+            var("ALIBABA_CLOUD_OIDC_PROVIDER_ARN")?,
+            var("ALIBABA_CLOUD_OIDC_TOKEN_FILE")?,
+        );
+        if let Some(role_session_name) = var("ALIBABA_CLOUD_ROLE_SESSION_NAME") {
+            provider = provider.role_session_name(role_session_name);
+        }
+        Some(provider)
+    }
+    /// Url of the STS service, `https://sts.aliyuncs.com` by default.
+    pub fn endpoint<S: Into<String>>(mut self, endpoint: S) -> Self {
+        self.options.endpoint = endpoint.into();
+        self
+    }
+    pub fn role_session_name<S: Into<String>>(mut self, role_session_name: S) -> Self {
+        self.options.role_session_name = role_session_name.into();
+        self
+    }
+    /// Validity of the assumed credentials, 1 hour by default.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.options.duration = duration;
+        self
+    }
+    /// A policy further restricting the permissions of the assumed role.
+    pub fn policy<S: Into<String>>(mut self, policy: S) -> Self {
+        self.options.policy = Some(policy.into());
+        self
+    }
+}
+
+#[async_trait]
+impl CredentialsProvider for OidcRoleProvider {
+    async fn credentials(&self) -> Result<Credentials, CredentialsError> {
+        self.cache
+            .get_or_fetch(|| self.assume_role_with_oidc())
+            .await
+    }
+}
+
+impl OidcRoleProvider {
+    async fn assume_role_with_oidc(&self) -> Result<Credentials, CredentialsError> {
+        let token = tokio::fs::read_to_string(&self.oidc_token_file)
+            .await
+            .map_err(|e| {
+                CredentialsError::with_source(
+                    format!(
+                        "failed to read OIDC token file {}",
+                        self.oidc_token_file.display()
+                    ),
+                    e,
+                )
+            })?;
+        // The OIDC token authenticates the call, it is not signed.
+        let mut params = self.options.params("AssumeRoleWithOIDC", &self.role_arn);
+        params.insert("OIDCProviderArn", self.oidc_provider_arn.clone());
+        params.insert("OIDCToken", token.trim().to_owned());
+        call(&self.options.endpoint, &params).await
+    }
+}
+
+#[derive(Clone, Debug)]
+struct StsOptions {
+    endpoint: String,
+    role_session_name: String,
+    duration: Duration,
+    policy: Option<String>,
+}
+
+impl Default for StsOptions {
+    fn default() -> Self {
+        Self {
+            endpoint: STS_ENDPOINT.to_owned(),
+            role_session_name: DEFAULT_SESSION_NAME.to_owned(),
+            duration: Duration::from_secs(3600),
+            policy: None,
+        }
+    }
+}
+
+impl StsOptions {
+    fn params(&self, action: &str, role_arn: &str) -> BTreeMap<&'static str, String> {
+        let mut params = BTreeMap::new();
+        params.insert("Action", action.to_owned());
+        params.insert("Format", "JSON".to_owned());
+        params.insert("Version", STS_VERSION.to_owned());
+        params.insert(
+            "Timestamp",
+            Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        );
+        params.insert("RoleArn", role_arn.to_owned());
+        params.insert("RoleSessionName", self.role_session_name.clone());
+        params.insert("DurationSeconds", self.duration.as_secs().to_string());
+        if let Some(ref policy) = self.policy {
+            params.insert("Policy", policy.clone());
+        }
+        params
+    }
+}
+
+/// Signature of an Alibaba Cloud RPC style API call: the HMAC-SHA1, keyed by
+/// the secret followed by `&`, of the method and the encoded sorted params.
+fn sign_rpc(method: &str, params: &BTreeMap<&str, String>, access_key_secret: &str) -> String {
+    let canonicalized = params
+        .iter()
+        .map(|(k, v)| format!("{}={}", encode_query(k), encode_query(v)))
+        .collect::<Vec<_>>()
+        .join("&");
+    let string_to_sign = format!(
+        "{}&{}&{}",
+        method,
+        encode_query("/"),
+        encode_query(&canonicalized)
+    );
+    let mut hasher = Hmac::new(Sha1::new(), format!("{}&", access_key_secret).as_bytes());
+    hasher.input(string_to_sign.as_bytes());
+    encode(hasher.result().code())
+}
+
+/// Unique per call, STS rejects replayed nonces.
+fn nonce() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_i64(Utc::now().timestamp_nanos_opt().unwrap_or_default());
+    format!("{:016x}", hasher.finish())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct StsResponse {
+    credentials: Option<StsCredentials>,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct StsCredentials {
+    access_key_id: String,
+    access_key_secret: String,
+    security_token: String,
+    expiration: String,
+}
+
+async fn call(
+    endpoint: &str,
+    params: &BTreeMap<&str, String>,
+) -> Result<Credentials, CredentialsError> {
+    let fail = |e| CredentialsError::with_source("failed to call STS", e);
+    let resp = reqwest_client()
+        .post(endpoint)
+        .form(params)
+        .send()
+        .await
+        .map_err(fail)?;
+    let status = resp.status();
+    let body = resp.text().await.map_err(fail)?;
+    let sts: StsResponse = serde_json::from_str(&body).map_err(|e| {
+        CredentialsError::with_source(format!("invalid STS response ({})", status), e)
+    })?;
+    let sts_credentials = match sts.credentials {
+        Some(sts_credentials) if status.is_success() => sts_credentials,
+        _ => {
+            return Err(CredentialsError::new(format!(
+                "STS {} responded {}: {} {}",
+                params["Action"],
+                status,
+                sts.code.unwrap_or_default(),
+                sts.message.unwrap_or_default()
+            )))
+        }
+    };
+    let expiration = sts_credentials
+        .expiration
+        .parse::<DateTime<Utc>>()
+        .map_err(|e| CredentialsError::with_source("invalid STS credentials expiration", e))?;
+    Ok(Credentials::new_with_token(
+        sts_credentials.access_key_id,
+        sts_credentials.access_key_secret,
+        sts_credentials.security_token,
+        expiration,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        credentials::StaticProvider,
+        test_util::{MockRequest, MockResponse, MockServer},
+    };
+
+    fn form(rqst: &MockRequest) -> BTreeMap<String, String> {
+        url::form_urlencoded::parse(&rqst.body)
+            .into_owned()
+            .collect()
+    }
+
+    fn sts_response(rqst: &MockRequest) -> MockResponse {
+        let form = form(rqst);
+        if form.get("RoleArn").map(String::as_str) != Some("acs:ram::123:role/oss") {
+            return MockResponse::status(400).body(
+                r#"{"RequestId": "id", "Code": "EntityNotExist.Role", "Message": "The role not exists."}"#,
+            );
+        }
+        MockResponse::ok(format!(
+            r#"{{
+                "RequestId": "id",
+                "AssumedRoleUser": {{"Arn": "acs:ram::123:role/oss/session", "AssumedRoleId": "1:session"}},
+                "Credentials": {{
+                    "AccessKeyId": "STS.{}",
+                    "AccessKeySecret": "sk",
+                    "SecurityToken": "token",
+                    "Expiration": "2030-11-01T05:20:01Z"
+                }}
+            }}"#,
+            form["Action"]
+        ))
+    }
+
+    #[test]
+    fn sign_rpc_test() {
+        let mut params = BTreeMap::new();
+        params.insert("Action", "AssumeRole".to_owned());
+        params.insert("RoleArn", "acs:ram::123:role/oss".to_owned());
+        params.insert("Timestamp", "2023-12-16T16:20:57Z".to_owned());
+        params.insert("RoleSessionName", "a b~c".to_owned());
+        assert_eq!(
+            sign_rpc("POST", &params, "sk"),
+            "Z03nSEu+2gmBsPt/70YP+ohud7Q="
+        );
+    }
+
+    #[tokio::test]
+    async fn assume_role_test() {
+        let server = MockServer::start(sts_response).await;
+        let provider = AssumeRoleProvider::new(
+            StaticProvider::from(Credentials::new_with_token(
+                "ak",
+                "sk",
+                "source-token",
+                None,
+            )),
+            "acs:ram::123:role/oss",
+        )
+        .endpoint(server.url("/"))
+        .role_session_name("session")
+        .duration(Duration::from_secs(900));
+        let credentials = provider.credentials().await.unwrap();
+        assert_eq!(credentials.access_key_id(), "STS.AssumeRole");
+        assert_eq!(credentials.security_token(), Some("token"));
+        assert_eq!(
+            credentials.expiration(),
+            Some("2030-11-01T05:20:01Z".parse().unwrap())
+        );
+        // Cached until shortly before the expiry.
+        assert_eq!(provider.credentials().await.unwrap(), credentials);
+        assert_eq!(server.requests().len(), 1);
+
+        let form = form(&server.requests()[0]);
+        assert_eq!(form["AccessKeyId"], "ak");
+        assert_eq!(form["SecurityToken"], "source-token");
+        assert_eq!(form["RoleSessionName"], "session");
+        assert_eq!(form["DurationSeconds"], "900");
+        let mut params: BTreeMap<&str, String> =
+            form.iter().map(|(k, v)| (k.as_str(), v.clone())).collect();
+        let signature = params.remove("Signature").unwrap();
+        assert_eq!(signature, sign_rpc("POST", &params, "sk"));
+
+        let provider = AssumeRoleProvider::new(StaticProvider::new("ak", "sk"), "missing")
+            .endpoint(server.url("/"));
+        let err = provider.credentials().await.unwrap_err();
+        assert!(err.to_string().contains("EntityNotExist.Role"));
+    }
+
+    #[tokio::test]
+    async fn oidc_role_test() {
+        let server = MockServer::start(sts_response).await;
+        let token_file = crate::test_util::temp_path("oidc_token");
+        std::fs::write(&token_file, "oidc-token\n").unwrap();
+        let provider = OidcRoleProvider::new(
+            "acs:ram::123:role/oss",
+            "acs:ram::123:oidc-provider/ack",
+            &token_file,
+        )
+        .endpoint(server.url("/"));
+        let credentials = provider.credentials().await.unwrap();
+        assert_eq!(credentials.access_key_id(), "STS.AssumeRoleWithOIDC");
+        // Cached until shortly before the expiry.
+        assert_eq!(provider.credentials().await.unwrap(), credentials);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        let form = form(&requests[0]);
+        assert_eq!(form["OIDCToken"], "oidc-token");
+        assert_eq!(form["OIDCProviderArn"], "acs:ram::123:oidc-provider/ack");
+        assert!(!form.contains_key("Signature"));
+        std::fs::remove_file(token_file).unwrap();
+    }
+}
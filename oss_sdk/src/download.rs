@@ -0,0 +1,540 @@
+use std::{
+    io::{self, SeekFrom},
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Instant,
+};
+
+use chrono::{DateTime, Utc};
+use crc::{Crc, CRC_64_XZ};
+use crypto::{digest::Digest, md5::Md5};
+use futures::{stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::{File, OpenOptions},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+    sync::Mutex,
+};
+
+use crate::{
+    checkpoint,
+    http_client::{errors, SignAndDispatch},
+    ByteStream, DownloadOptions, GetObjectRequest, OSSClient, ObjectMeta, Progress, Result,
+};
+
+/// The CRC-64/ECMA-182 variant of `x-oss-hash-crc64ecma`.
+pub(crate) const CRC64: Crc<u64> = Crc::<u64>::new(&CRC_64_XZ);
+
+/// Progress of a resumable download, saved as JSON.
+#[derive(Debug, Serialize, Deserialize)]
+struct DownloadCheckpoint {
+    bucket: String,
+    key: String,
+    /// ETag, size and last-modified time of the object when the download
+    /// started.
+    etag: String,
+    size: u64,
+    last_modified: Option<DateTime<Utc>>,
+    part_size: u64,
+    /// Indexes of the ranges written to the temporary file.
+    parts: Vec<u64>,
+}
+
+impl DownloadCheckpoint {
+    fn is_of(&self, bucket: &str, key: &str, meta: &ObjectMeta) -> bool {
+        self.bucket == bucket
+            && self.key == key
+            && self.etag == meta.etag
+            && self.size == meta.size
+            && self.last_modified == meta.last_modified
+    }
+    /// First and last byte of range `index`.
+    fn range(&self, index: u64) -> (u64, u64) {
+        let start = index * self.part_size;
+        (start, (start + self.part_size).min(self.size) - 1)
+    }
+}
+
+impl<C: SignAndDispatch + Send + Sync> OSSClient<C> {
+    /// Downloads `key` to the file at `path`, fetching ranges of it
+    /// concurrently. They are written to a temporary file next to `path`,
+    /// which replaces it once complete and checked against the CRC64 of the
+    /// object or, for objects without one, against an MD5 ETag. Returns the
+    /// metadata of the downloaded object.
+    pub async fn download_file<K, P>(
+        &self,
+        key: K,
+        path: P,
+        options: DownloadOptions,
+    ) -> Result<ObjectMeta>
+    where
+        K: Into<String>,
+        P: AsRef<Path>,
+    {
+        let key = key.into();
+        let path = path.as_ref();
+        let meta = self.head_object(key.as_str()).await?;
+        let tmp_path = checkpoint::next_to(path, ".osstmp");
+        let checkpoint_path = match options.checkpoint_path {
+            Some(ref checkpoint_path) => checkpoint_path.clone(),
+            None => checkpoint::next_to(path, ".ossdownload"),
+        };
+
+        let mut resumed = None;
+        if options.resumable {
+            resumed = checkpoint::load::<DownloadCheckpoint>(&checkpoint_path)
+                .await
+                .filter(|checkpoint| checkpoint.is_of(self.get_bucket(), &key, &meta));
+        }
+        let resumed = match resumed {
+            Some(checkpoint) => match tokio::fs::metadata(&tmp_path).await {
+                Ok(tmp) if tmp.len() == meta.size => Some(checkpoint),
+                _ => None,
+            },
+            None => None,
+        };
+        let checkpoint = match resumed {
+            Some(checkpoint) => checkpoint,
+            None => {
+                File::create(&tmp_path).await?.set_len(meta.size).await?;
+                DownloadCheckpoint {
+                    bucket: self.get_bucket().to_owned(),
+                    key: key.clone(),
+                    etag: meta.etag.clone(),
+                    size: meta.size,
+                    last_modified: meta.last_modified,
+                    part_size: options.part_size.max(1),
+                    parts: Vec::new(),
+                }
+            }
+        };
+
+        let result = async {
+            if options.resumable {
+                checkpoint::save(&checkpoint_path, &checkpoint).await?;
+            }
+            self.download_ranges(
+                &key,
+                &tmp_path,
+                &checkpoint_path,
+                checkpoint,
+                &meta,
+                &options,
+            )
+            .await
+        }
+        .await;
+        if let Err(e) = result {
+            if !options.resumable {
+                let _ = tokio::fs::remove_file(&tmp_path).await;
+            }
+            return Err(e);
+        }
+        // A download failing verification is corrupt, not partial.
+        if let Err(e) = verify(&tmp_path, &meta).await {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            let _ = checkpoint::remove(&checkpoint_path).await;
+            return Err(e);
+        }
+        tokio::fs::rename(&tmp_path, path).await?;
+        checkpoint::remove(&checkpoint_path).await?;
+        Ok(meta)
+    }
+
+    /// Fetches the ranges of `key` missing from `checkpoint` into the file at
+    /// `tmp_path`, `options.concurrency` at a time.
+    async fn download_ranges(
+        &self,
+        key: &str,
+        tmp_path: &Path,
+        checkpoint_path: &Path,
+        checkpoint: DownloadCheckpoint,
+        meta: &ObjectMeta,
+        options: &DownloadOptions,
+    ) -> Result<()> {
+        let pending: Vec<u64> = (0..meta.size.div_ceil(checkpoint.part_size))
+            .filter(|index| !checkpoint.parts.contains(index))
+            .collect();
+        let transferred = checkpoint
+            .parts
+            .iter()
+            .map(|index| {
+                let (first, last) = checkpoint.range(*index);
+                last - first + 1
+            })
+            .sum();
+        let transferred = &AtomicU64::new(transferred);
+        let checkpoint = &Mutex::new(checkpoint);
+        stream::iter(pending)
+            .map(|index| async move {
+                let (first, last) = checkpoint.lock().await.range(index);
+                self.download_range(key, tmp_path, meta, first, last)
+                    .await?;
+                if options.resumable {
+                    let mut checkpoint = checkpoint.lock().await;
+                    checkpoint.parts.push(index);
+                    checkpoint::save(checkpoint_path, &*checkpoint).await?;
+                }
+                let len = last - first + 1;
+                let transferred = transferred.fetch_add(len, Ordering::Relaxed) + len;
+                if let Some(ref progress) = options.progress {
+                    progress.call(Progress {
+                        transferred,
+                        total: Some(meta.size),
+                    });
+                }
+                Ok(())
+            })
+            .buffer_unordered(options.concurrency.max(1))
+            .try_collect()
+            .await
+    }
+
+    /// Writes bytes `first` to `last` of `key` at the same offset of
+    /// the file at `tmp_path`. A body cut off after the response arrived is
+    /// beyond what `sign_and_dispatch` retries, so the range is fetched again
+    /// here under the same retry policy.
+    async fn download_range(
+        &self,
+        key: &str,
+        tmp_path: &Path,
+        meta: &ObjectMeta,
+        first: u64,
+        last: u64,
+    ) -> Result<()> {
+        let request = GetObjectRequest {
+            range: Some((first, Some(last))),
+            range_behavior_standard: true,
+            // Fails with 412 should the object be replaced meanwhile.
+            if_match: Some(meta.etag.clone()).filter(|etag| !etag.is_empty()),
+            ..GetObjectRequest::new(key)
+        };
+        let policy = self.get_retry_policy();
+        let started = Instant::now();
+        let mut attempt = 1;
+        loop {
+            let body = self.get_object(request.clone()).await?.body;
+            let delay = policy.delay(attempt);
+            let in_time = policy
+                .deadline
+                .is_none_or(|deadline| started.elapsed() + delay < deadline);
+            match write_range(body, tmp_path, first, last).await {
+                Err(e) if attempt < policy.max_attempts && in_time && e.is_retryable() => {
+                    log::debug!("fetching range {}-{} again: {}", first, last, e);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Writes `body`, bytes `first` to `last` of the object, at the same offset
+/// of the file at `tmp_path`.
+async fn write_range(mut body: ByteStream, tmp_path: &Path, first: u64, last: u64) -> Result<()> {
+    let mut file = OpenOptions::new().write(true).open(tmp_path).await?;
+    file.seek(SeekFrom::Start(first)).await?;
+    let mut written = 0;
+    while let Some(chunk) = body.try_next().await.map_err(errors::read)? {
+        file.write_all(&chunk).await?;
+        written += chunk.len() as u64;
+    }
+    file.flush().await?;
+    if written != last - first + 1 {
+        return Err(
+            errors::body(format!("got {} bytes of range {}-{}", written, first, last)).into(),
+        );
+    }
+    Ok(())
+}
+
+/// Checks the file at `path` against the CRC64 of the object, or against
+/// its ETag when it is the MD5 of the content.
+async fn verify(path: &Path, meta: &ObjectMeta) -> Result<()> {
+    let etag = meta.etag.trim_matches('"');
+    let is_md5 = etag.len() == 32 && etag.bytes().all(|b| b.is_ascii_hexdigit());
+    if meta.crc64.is_none() && !is_md5 {
+        return Ok(());
+    }
+
+    let mut file = File::open(path).await?;
+    let mut crc64 = CRC64.digest();
+    let mut md5 = Md5::new();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        match meta.crc64 {
+            Some(_) => crc64.update(&buf[..n]),
+            None => md5.input(&buf[..n]),
+        }
+    }
+    let mismatch = match meta.crc64 {
+        Some(expected) => {
+            let actual = crc64.finalize();
+            (expected != actual).then(|| format!("CRC64 {} instead of {}", actual, expected))
+        }
+        None => {
+            let actual = md5.result_str();
+            (!actual.eq_ignore_ascii_case(etag))
+                .then(|| format!("MD5 {} instead of ETag {}", actual, etag))
+        }
+    };
+    match mismatch {
+        Some(mismatch) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("corrupt download of {}: {}", path.display(), mismatch),
+        )
+        .into()),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, AtomicUsize},
+        Arc, Mutex,
+    };
+
+    use super::*;
+    use crate::{
+        test_util::{mock_client, temp_path, MockRequest, MockResponse, MockServer},
+        ProgressCallback, RetryPolicy,
+    };
+
+    const PART_SIZE: u64 = 100 * 1024;
+
+    fn object() -> Vec<u8> {
+        (0..3 * PART_SIZE - 100).map(|i| (i % 251) as u8).collect()
+    }
+
+    /// Serves `object()` with the given ETag and CRC64, failing the range
+    /// starting at `2 * PART_SIZE` with a 403 while `fatal` is set.
+    fn object_server(
+        etag: String,
+        crc64: Option<u64>,
+        fatal: Arc<AtomicBool>,
+    ) -> impl Fn(&MockRequest) -> MockResponse {
+        let data = object();
+        move |rqst| {
+            let mut resp = match rqst.header("range") {
+                _ if rqst.method == "HEAD" => MockResponse::ok(data.clone()),
+                _ if rqst.header("if-match") != Some(etag.as_str()) => MockResponse::status(412),
+                Some(range) => {
+                    let (first, last) = range.trim_start_matches("bytes=").split_once('-').unwrap();
+                    let (first, last): (usize, usize) =
+                        (first.parse().unwrap(), last.parse().unwrap());
+                    if first as u64 == 2 * PART_SIZE && fatal.load(Ordering::SeqCst) {
+                        return MockResponse::status(403);
+                    }
+                    MockResponse::status(206).body(&data[first..=last])
+                }
+                None => MockResponse::status(400),
+            };
+            resp = resp.header("etag", etag.clone());
+            if let Some(crc64) = crc64 {
+                resp = resp.header("x-oss-hash-crc64ecma", crc64.to_string());
+            }
+            resp
+        }
+    }
+
+    #[tokio::test]
+    async fn download_file_test() {
+        let crc64 = CRC64.checksum(&object());
+        let fatal = Arc::new(AtomicBool::new(false));
+        let server =
+            MockServer::start(object_server("\"etag\"".to_owned(), Some(crc64), fatal)).await;
+        let oss_instance = mock_client(&server);
+        let path = temp_path("download");
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let recorded = progress.clone();
+        let options = DownloadOptions {
+            part_size: PART_SIZE,
+            concurrency: 2,
+            progress: Some(ProgressCallback::new(move |p: Progress| {
+                recorded.lock().unwrap().push(p)
+            })),
+            ..Default::default()
+        };
+
+        let meta = oss_instance
+            .download_file("object", &path, options)
+            .await
+            .unwrap();
+        assert_eq!(meta.crc64, Some(crc64));
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), object());
+        tokio::fs::remove_file(&path).await.unwrap();
+        assert!(!checkpoint::next_to(&path, ".osstmp").exists());
+
+        let progress = progress.lock().unwrap();
+        assert_eq!(progress.len(), 3);
+        assert_eq!(
+            progress.last(),
+            Some(&Progress {
+                transferred: 3 * PART_SIZE - 100,
+                total: Some(3 * PART_SIZE - 100)
+            })
+        );
+        let mut ranges: Vec<String> = server
+            .requests()
+            .iter()
+            .filter_map(|rqst| rqst.header("range").map(str::to_owned))
+            .collect();
+        ranges.sort();
+        assert_eq!(
+            ranges,
+            vec![
+                "bytes=0-102399",
+                "bytes=102400-204799",
+                "bytes=204800-307099"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn resumable_download_test() {
+        let crc64 = CRC64.checksum(&object());
+        let fatal = Arc::new(AtomicBool::new(true));
+        let server = MockServer::start(object_server(
+            "\"etag\"".to_owned(),
+            Some(crc64),
+            fatal.clone(),
+        ))
+        .await;
+        let oss_instance = mock_client(&server);
+        let path = temp_path("resumable_download");
+        let checkpoint_path = checkpoint::next_to(&path, ".ossdownload");
+        // One range at a time, so the first two are done when the third fails
+        let options = DownloadOptions {
+            part_size: PART_SIZE,
+            concurrency: 1,
+            resumable: true,
+            ..Default::default()
+        };
+
+        let err = oss_instance
+            .download_file("object", &path, options.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(err.status().map(|s| s.as_u16()), Some(403));
+        assert!(!path.exists());
+        let saved: DownloadCheckpoint = checkpoint::load(&checkpoint_path).await.unwrap();
+        assert_eq!(saved.parts, vec![0, 1]);
+        let interrupted = server.requests().len();
+
+        fatal.store(false, Ordering::SeqCst);
+        let ranges = Arc::new(AtomicUsize::new(0));
+        let counted = ranges.clone();
+        let options = DownloadOptions {
+            progress: Some(ProgressCallback::new(move |p: Progress| {
+                assert_eq!(p.transferred, 3 * PART_SIZE - 100);
+                counted.fetch_add(1, Ordering::SeqCst);
+            })),
+            ..options
+        };
+        oss_instance
+            .download_file("object", &path, options)
+            .await
+            .unwrap();
+        assert_eq!(ranges.load(Ordering::SeqCst), 1);
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), object());
+        assert!(!checkpoint_path.exists());
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        let requests = &server.requests()[interrupted..];
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].header("range"), Some("bytes=204800-307099"));
+    }
+
+    #[tokio::test]
+    async fn truncated_range_test() {
+        let crc64 = CRC64.checksum(&object());
+        let inner = object_server(
+            "\"etag\"".to_owned(),
+            Some(crc64),
+            Arc::new(AtomicBool::new(false)),
+        );
+        let truncated = Arc::new(AtomicUsize::new(0));
+        let counted = truncated.clone();
+        // Cuts the body of the second range short, every time
+        let server = MockServer::start(move |rqst| {
+            let resp = inner(rqst);
+            if rqst.header("range") != Some("bytes=102400-204799") {
+                return resp;
+            }
+            counted.fetch_add(1, Ordering::SeqCst);
+            let len = resp.body.len().to_string();
+            let body = resp.body[..100].to_vec();
+            resp.header("content-length", len).body(body)
+        })
+        .await;
+        let mut oss_instance = mock_client(&server);
+        oss_instance.set_retry_policy(RetryPolicy {
+            base_delay: std::time::Duration::from_millis(1),
+            ..Default::default()
+        });
+        let path = temp_path("truncated_range");
+        let options = DownloadOptions {
+            part_size: PART_SIZE,
+            ..Default::default()
+        };
+
+        let err = oss_instance
+            .download_file("object", &path, options.clone())
+            .await
+            .unwrap_err();
+        assert!(err.is_retryable(), "{}", err);
+        assert_eq!(truncated.load(Ordering::SeqCst), 3);
+
+        truncated.store(0, Ordering::SeqCst);
+        oss_instance.set_retry_policy(RetryPolicy::none());
+        oss_instance
+            .download_file("object", &path, options)
+            .await
+            .unwrap_err();
+        assert_eq!(truncated.load(Ordering::SeqCst), 1);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn corrupt_download_test() {
+        let md5 = {
+            let mut md5 = Md5::new();
+            md5.input(&object());
+            md5.result_str().to_uppercase()
+        };
+        let path = temp_path("corrupt_download");
+
+        // Objects without a CRC64 are checked against their MD5 ETag
+        let server = MockServer::start(object_server(
+            format!("\"{}\"", md5),
+            None,
+            Arc::new(AtomicBool::new(false)),
+        ))
+        .await;
+        mock_client(&server)
+            .download_file("object", &path, DownloadOptions::default())
+            .await
+            .unwrap();
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        let server = MockServer::start(object_server(
+            "\"etag\"".to_owned(),
+            Some(42),
+            Arc::new(AtomicBool::new(false)),
+        ))
+        .await;
+        let err = mock_client(&server)
+            .download_file("object", &path, DownloadOptions::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("CRC64"), "{}", err);
+        assert!(!path.exists());
+        assert!(!checkpoint::next_to(&path, ".osstmp").exists());
+    }
+}
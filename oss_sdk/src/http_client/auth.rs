@@ -1,5 +1,6 @@
 use super::errors::HttpResult;
 use super::*;
+use crate::statics::{CONTENT_MD5, CONTENT_TYPE, OSS_CANONICALIZED_PREFIX};
 use base64::encode;
 use chrono::Utc;
 
@@ -8,8 +9,9 @@ use crypto::mac::Mac;
 use crypto::sha1::Sha1;
 use http::header::HeaderName;
 use http::HeaderValue;
+use url::Url;
 
-const RESOURCES: [&str; 51] = [
+const RESOURCES: [&str; 52] = [
     "acl",
     "uploads",
     "location",
@@ -34,6 +36,7 @@ const RESOURCES: [&str; 51] = [
     "replicationLocation",
     "cname",
     "bucketInfo",
+    "stat",
     "comp",
     "qos",
     "live",
@@ -63,39 +66,73 @@ const RESOURCES: [&str; 51] = [
     "continuation-token",
 ];
 
+const OSS_SECURITY_TOKEN: &str = "x-oss-security-token";
+
 impl SignedRequest {
-    pub(crate) fn oss_sign(&mut self) -> HttpResult<()> {
+    pub(crate) fn oss_sign(&mut self, credentials: &Credentials) -> HttpResult<()> {
+        if let Some(token) = credentials.security_token() {
+            let token = HeaderValue::from_str(token).map_err(errors::header)?;
+            self.add_header(HeaderName::from_static(OSS_SECURITY_TOKEN), token);
+        }
+        if self.get_signature_version() == SignatureVersion::V4 {
+            return self.oss_sign_v4(credentials, Utc::now());
+        }
+        let date = Utc::now().format("%a, %d %b %Y %T GMT").to_string();
         self.add_header(
             HeaderName::from_static("date"),
-            HeaderValue::from_str(&Utc::now().format("%a, %d %b %Y %T GMT").to_string())
-                .map_err(errors::header)?,
+            HeaderValue::from_str(&date).map_err(errors::header)?,
         );
-        let (auth_key, auth_value) = self.authorization_header()?;
-        self.add_header(auth_key, auth_value);
+        let signature = self.signature_v1(credentials, &date)?;
+        let authorization = HeaderValue::from_str(&format!(
+            "OSS {}:{}",
+            credentials.access_key_id(),
+            signature
+        ))
+        .map_err(errors::header)?;
+        self.add_header(HeaderName::from_static("authorization"), authorization);
         Ok(())
     }
-    fn authorization_header(&self) -> HttpResult<(HeaderName, HeaderValue)> {
+
+    /// Moves a V1 signature into the query string, `expires` being a unix timestamp.
+    /// Headers of the request are signed and have to be sent along with the url.
+    pub(crate) fn presign_v1(
+        &mut self,
+        credentials: &Credentials,
+        expires: u64,
+    ) -> HttpResult<Url> {
+        if let Some(token) = credentials.security_token() {
+            self.add_params("security-token", token);
+        }
+        let signature = self.signature_v1(credentials, &expires.to_string())?;
+        self.add_params("OSSAccessKeyId", credentials.access_key_id());
+        self.add_params("Expires", expires.to_string().as_str());
+        self.add_params("Signature", signature.as_str());
+        self.generate_url()
+    }
+
+    /// Base64 HMAC-SHA1 of the V1 string to sign, `date` being either the
+    /// `Date` header or the `Expires` of a presigned url.
+    fn signature_v1(&self, credentials: &Credentials, date: &str) -> HttpResult<String> {
         let headers = &self.headers;
-        let date = headers
-            .get("date")
-            .and_then(|val| val.to_str().ok())
-            .unwrap_or_default();
         let content_type = headers
-            .get("content-type")
+            .get(CONTENT_TYPE)
             .and_then(|val| val.to_str().ok())
             .unwrap_or_default();
-
         let content_md5 = headers
-            .get("Content-MD5")
-            .map(|val| encode(val.to_str().ok().unwrap_or_default()))
+            .get(CONTENT_MD5)
+            .and_then(|val| val.to_str().ok())
             .unwrap_or_default();
 
-        let mut oss_headers_str = String::new();
-        for (k, v) in headers
+        let mut oss_headers: Vec<(&str, &str)> = headers
             .iter()
-            .filter(|(k, _)| k.as_str().contains("x-oss-"))
-        {
-            oss_headers_str += &format!("{}:{}\n", k, v.to_str().map_err(errors::header)?);
+            .filter(|(k, _)| k.as_str().starts_with(OSS_CANONICALIZED_PREFIX))
+            .map(|(k, v)| v.to_str().map(|v| (k.as_str(), v)))
+            .collect::<std::result::Result<_, _>>()
+            .map_err(errors::header)?;
+        oss_headers.sort_unstable();
+        let mut oss_headers_str = String::new();
+        for (k, v) in oss_headers {
+            oss_headers_str += &format!("{}:{}\n", k, v);
         }
 
         let oss_resource_str = get_oss_resource_str(&self.bucket, &self.object, &self.params);
@@ -104,14 +141,9 @@ impl SignedRequest {
             &self.method, content_md5, content_type, date, oss_headers_str, oss_resource_str
         );
 
-        let mut hasher = Hmac::new(Sha1::new(), &self.access_key_secret.as_bytes());
+        let mut hasher = Hmac::new(Sha1::new(), credentials.access_key_secret().as_bytes());
         hasher.input(sign_str.as_bytes());
-        let sign_str_base64 = encode(hasher.result().code());
-
-        let authorization =
-            HeaderValue::from_str(&format!("OSS {}:{}", &self.access_key_id, sign_str_base64))
-                .map_err(errors::header)?;
-        Ok((HeaderName::from_static("authorization"), authorization))
+        Ok(encode(hasher.result().code()))
     }
 }
 
@@ -142,9 +174,95 @@ fn get_resources_str(params: &Params) -> String {
 #[inline]
 fn get_oss_resource_str(bucket: &str, object: &str, params: &Params) -> String {
     let oss_resources = get_resources_str(params);
-    if bucket == "" {
+    if bucket.is_empty() {
         format!("/{}{}", object, oss_resources)
     } else {
         format!("/{}/{}{}", bucket, object, oss_resources)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::Method;
+
+    // Request of the OSS V1 signature documentation, the expected value is the
+    // HMAC-SHA1 of its documented string to sign.
+    #[test]
+    fn signature_v1_test() {
+        let mut rqst = SignedRequest::new(
+            Method::PUT,
+            &Region::HangZhou,
+            "oss-example",
+            "nelson",
+            Schema::Http,
+        );
+        for (k, v) in [
+            (
+                "content-md5",
+                "ODBGOERFMDMzQTczRUY3NUE3NzA5QzdFNUYzMDQxNEM=",
+            ),
+            ("content-type", "text/html"),
+            ("x-oss-meta-author", "foo@example.com"),
+            ("x-oss-magic", "abracadabra"),
+        ]
+        .iter()
+        {
+            rqst.add_header(HeaderName::from_static(k), HeaderValue::from_static(v));
+        }
+        assert_eq!(
+            rqst.signature_v1(
+                &Credentials::new(
+                    "44CF9590006BF252F707",
+                    "OtxrzxIsfpFjA7SwPzILwy8Bw21TLhquhboDYROV"
+                ),
+                "Thu, 17 Nov 2005 18:49:58 GMT"
+            )
+            .unwrap(),
+            "fV5fq7DPwNbrrig7nvUSZIVyruI="
+        );
+    }
+
+    #[test]
+    fn security_token_test() {
+        let mut rqst = SignedRequest::new(
+            Method::GET,
+            &Region::HangZhou,
+            "bucket",
+            "object",
+            Schema::Https,
+        );
+        let credentials = Credentials::new_with_token("ak", "sk", "token", None);
+        let mut presigned = rqst.try_clone().unwrap();
+        let url = presigned.presign_v1(&credentials, 1700000000).unwrap();
+        assert!(url
+            .query_pairs()
+            .any(|(k, v)| k == "security-token" && v == "token"));
+        assert_eq!(
+            get_oss_resource_str("bucket", "object", &presigned.params),
+            "/bucket/object?security-token=token"
+        );
+
+        rqst.oss_sign(&credentials).unwrap();
+        assert_eq!(rqst.headers.get("x-oss-security-token").unwrap(), "token");
+        assert!(rqst
+            .headers
+            .get("authorization")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("OSS ak:"));
+    }
+
+    #[test]
+    fn oss_resource_test() {
+        let mut params = Params::new();
+        params.insert("prefix".to_owned(), Some("my".to_owned()));
+        params.insert("marker".to_owned(), Some("bucket-2".to_owned()));
+        // Service-level requests, such as ListBuckets, sign the root
+        assert_eq!(get_oss_resource_str("", "", &params), "/");
+
+        params.insert("stat".to_owned(), None);
+        assert_eq!(get_oss_resource_str("bucket", "", &params), "/bucket/?stat");
+    }
+}
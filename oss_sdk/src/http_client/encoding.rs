@@ -0,0 +1,61 @@
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+/// Everything but the RFC 3986 unreserved characters `A-Za-z0-9-_.~`,
+/// which is what OSS expects in paths and query strings.
+const URI_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// Same as `URI_COMPONENT` but keeps `/` so object keys keep their "directories".
+const URI_PATH: &AsciiSet = &URI_COMPONENT.remove(b'/');
+
+/// Percent-encodes an object key for the url path, `/` is kept as is.
+pub(crate) fn encode_key(key: &str) -> String {
+    utf8_percent_encode(key, URI_PATH).to_string()
+}
+
+/// Percent-encodes a query parameter name or value, `/` included.
+pub(crate) fn encode_query(s: &str) -> String {
+    utf8_percent_encode(s, URI_COMPONENT).to_string()
+}
+
+/// Reverses the `encoding-type=url` encoding of keys in listings.
+pub(crate) fn decode(s: &str) -> String {
+    percent_decode_str(s).decode_utf8_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEYS: [&str; 9] = [
+        "plain.txt",
+        "dir/sub dir/file name.txt",
+        "a+b=c&d",
+        "100%real#fragment?query",
+        "中文/文件名.pdf",
+        "~tilde_-.",
+        "emoji😀",
+        "//leading/and/trailing//",
+        "semi;colon,comma:colon@at!bang'quote(paren)*star",
+    ];
+
+    #[test]
+    fn encode_key_test() {
+        assert_eq!(encode_key("dir/a b+c%d#e?f"), "dir/a%20b%2Bc%25d%23e%3Ff");
+        assert_eq!(encode_key("中"), "%E4%B8%AD");
+        assert_eq!(encode_query("a/b c"), "a%2Fb%20c");
+        for key in KEYS.iter() {
+            let encoded = encode_key(key);
+            assert!(encoded
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"-_.~/%".contains(&b)));
+            assert_eq!(&decode(&encoded), key);
+            let encoded = encode_query(key);
+            assert!(!encoded.contains('/'));
+            assert_eq!(&decode(&encoded), key);
+        }
+    }
+}
@@ -1,6 +1,5 @@
-use std::{error::Error as StdError, fmt};
+use std::{error::Error as StdError, fmt, io};
 
-use http::StatusCode;
 use url::Url;
 
 pub(crate) type HttpResult<T> = Result<T, HttpError>;
@@ -24,16 +23,27 @@ impl HttpError {
             url: None,
         }
     }
+    /// Network failures, which may go away when the request is sent again,
+    /// unlike an invalid url or a redirect loop.
+    pub(crate) fn is_retryable(&self) -> bool {
+        matches!(self.kind, Kind::Body | Kind::Client | Kind::Timeout)
+    }
+    pub(crate) fn is_timeout(&self) -> bool {
+        matches!(self.kind, Kind::Timeout)
+    }
 }
 
 #[derive(Debug)]
 pub(crate) enum Kind {
     Body,
     Client,
-    Status(StatusCode),
+    Timeout,
     Header,
     Url,
     Method,
+    Credentials,
+    /// Other failures of the HTTP client, which fail the same way every time.
+    Other,
 }
 
 pub(crate) fn url<E: Into<BoxedError>>(e: E) -> HttpError {
@@ -42,15 +52,31 @@ pub(crate) fn url<E: Into<BoxedError>>(e: E) -> HttpError {
 pub(crate) fn body<E: Into<BoxedError>>(e: E) -> HttpError {
     HttpError::new(Kind::Body, Some(e))
 }
+/// Failure to read a response body, a timeout if the read timed out.
+pub(crate) fn read(e: io::Error) -> HttpError {
+    match e.kind() {
+        io::ErrorKind::TimedOut => timeout(e),
+        _ => body(e),
+    }
+}
 pub(crate) fn header<E: Into<BoxedError>>(e: E) -> HttpError {
     HttpError::new(Kind::Header, Some(e))
 }
 pub(crate) fn method<E: Into<BoxedError>>(e: E) -> HttpError {
     HttpError::new(Kind::Method, Some(e))
 }
+pub(crate) fn credentials<E: Into<BoxedError>>(e: E) -> HttpError {
+    HttpError::new(Kind::Credentials, Some(e))
+}
 pub(crate) fn client<E: Into<BoxedError>>(e: E) -> HttpError {
     HttpError::new(Kind::Client, Some(e))
 }
+pub(crate) fn other<E: Into<BoxedError>>(e: E) -> HttpError {
+    HttpError::new(Kind::Other, Some(e))
+}
+pub(crate) fn timeout<E: Into<BoxedError>>(e: E) -> HttpError {
+    HttpError::new(Kind::Timeout, Some(e))
+}
 
 impl fmt::Debug for HttpError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -84,16 +110,13 @@ impl fmt::Display for HttpError {
 
         match self.kind {
             Kind::Body => f.write_str("request or response body error")?,
-            Kind::Status(ref code) => {
-                let prefix = if code.is_client_error() {
-                    "HTTP status client error"
-                } else {
-                    debug_assert!(code.is_server_error());
-                    "HTTP status server error"
-                };
-                write!(f, "{} ({})", prefix, code)?;
-            }
-            _ => unimplemented!(),
+            Kind::Client => f.write_str("error sending request")?,
+            Kind::Timeout => f.write_str("operation timed out")?,
+            Kind::Header => f.write_str("invalid header")?,
+            Kind::Url => f.write_str("invalid url")?,
+            Kind::Method => f.write_str("invalid method")?,
+            Kind::Credentials => f.write_str("failed to obtain credentials")?,
+            Kind::Other => f.write_str("HTTP client error")?,
         };
 
         ForUrl(self.url.as_ref()).fmt(f)?;
@@ -106,6 +129,12 @@ impl fmt::Display for HttpError {
     }
 }
 
+impl From<http::method::InvalidMethod> for HttpError {
+    fn from(e: http::method::InvalidMethod) -> Self {
+        method(e)
+    }
+}
+
 impl StdError for HttpError {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         self.source.as_ref().map(|e| &**e as _)
@@ -2,13 +2,15 @@ use super::*;
 use std::collections::BTreeMap;
 
 mod auth;
-mod errors;
+mod auth_v4;
+pub(crate) mod encoding;
+pub(crate) mod errors;
 mod requests;
 mod responses;
 mod sign_and_dispatch;
 
 pub use errors::HttpError;
-pub use requests::SignedRequest;
+pub use requests::{SignedRequest, SignedRequestPayload};
 pub use responses::HttpResponse;
 pub use sign_and_dispatch::SignAndDispatch;
 
@@ -1,8 +1,81 @@
-use super::{errors::HttpResult, *};
+use super::{
+    encoding::{encode_key, encode_query},
+    errors::HttpResult,
+    *,
+};
+use bytes::Bytes;
 use http::{header::HeaderName, HeaderMap, HeaderValue, Method};
+use std::{fmt, time::Duration};
 use url::Url;
 
-#[derive(Clone, Debug, Default)]
+/// Body of a request.
+pub enum SignedRequestPayload {
+    /// Held in memory, so it can be sent again on a retry
+    Buffer(Bytes),
+    /// Read as it is sent, so it can only be sent once
+    Stream(ByteStream),
+}
+impl SignedRequestPayload {
+    /// Length of the body, if known in advance.
+    pub fn size_hint(&self) -> Option<usize> {
+        match self {
+            SignedRequestPayload::Buffer(buf) => Some(buf.len()),
+            SignedRequestPayload::Stream(stream) => stream.size_hint(),
+        }
+    }
+    /// `None` for streams, which cannot be rewound.
+    pub fn try_clone(&self) -> Option<Self> {
+        match self {
+            SignedRequestPayload::Buffer(buf) => Some(SignedRequestPayload::Buffer(buf.clone())),
+            SignedRequestPayload::Stream(_) => None,
+        }
+    }
+}
+impl fmt::Debug for SignedRequestPayload {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SignedRequestPayload::Buffer(buf) => write!(f, "<Buffer len={}>", buf.len()),
+            SignedRequestPayload::Stream(stream) => stream.fmt(f),
+        }
+    }
+}
+impl From<Bytes> for SignedRequestPayload {
+    fn from(buf: Bytes) -> Self {
+        SignedRequestPayload::Buffer(buf)
+    }
+}
+impl From<Vec<u8>> for SignedRequestPayload {
+    fn from(buf: Vec<u8>) -> Self {
+        SignedRequestPayload::Buffer(buf.into())
+    }
+}
+impl From<Box<[u8]>> for SignedRequestPayload {
+    fn from(buf: Box<[u8]>) -> Self {
+        SignedRequestPayload::Buffer(buf.into())
+    }
+}
+impl From<String> for SignedRequestPayload {
+    fn from(buf: String) -> Self {
+        SignedRequestPayload::Buffer(buf.into())
+    }
+}
+impl From<&'static str> for SignedRequestPayload {
+    fn from(buf: &'static str) -> Self {
+        SignedRequestPayload::Buffer(Bytes::from_static(buf.as_bytes()))
+    }
+}
+impl From<&'static [u8]> for SignedRequestPayload {
+    fn from(buf: &'static [u8]) -> Self {
+        SignedRequestPayload::Buffer(Bytes::from_static(buf))
+    }
+}
+impl From<ByteStream> for SignedRequestPayload {
+    fn from(stream: ByteStream) -> Self {
+        SignedRequestPayload::Stream(stream)
+    }
+}
+
+#[derive(Debug, Default)]
 pub struct SignedRequest {
     pub method: Method,
     pub region: Region,
@@ -10,34 +83,33 @@ pub struct SignedRequest {
     pub object: String,
     pub headers: HeaderMap,
     pub params: Params,
-    pub payload: Option<Box<[u8]>>,
-    pub access_key_id: String,
-    pub access_key_secret: String,
+    pub payload: Option<SignedRequestPayload>,
     pub url: Option<Url>,
     schema: Schema,
+    endpoint_type: EndpointType,
+    addressing_style: AddressingStyle,
+    signature_version: SignatureVersion,
+    pub(crate) additional_headers: Vec<String>,
+    timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    idempotent: Option<bool>,
 }
 impl SignedRequest {
-    pub fn new<M, S1, S2, S3, S4>(
+    pub fn new<M, S1, S2>(
         method: M,
         region: &Region,
         bucket: S1,
         object: S2,
-        access_key_id: S3,
-        access_key_secret: S4,
         schema: Schema,
     ) -> Self
     where
         M: Into<Method>,
         S1: Into<String>,
         S2: Into<String>,
-        S3: Into<String>,
-        S4: Into<String>,
     {
         Self {
             method: method.into(),
             region: region.clone(),
-            access_key_id: access_key_id.into(),
-            access_key_secret: access_key_secret.into(),
             bucket: bucket.into(),
             object: object.into(),
             schema,
@@ -77,16 +149,51 @@ impl SignedRequest {
         self.params
             .insert(key.into(), val.into().map(|s| s.to_owned()));
     }
-    pub fn load<P>(&mut self, payload: P) -> usize
+    /// Sets the body and its Content-Length, left out (sending it chunked)
+    /// for streams of unknown length. Returns the length if known.
+    pub fn load<P>(&mut self, payload: P) -> Option<usize>
     where
-        P: Into<Box<[u8]>>,
+        P: Into<SignedRequestPayload>,
     {
-        self.payload = Some(payload.into());
-        self.payload.as_ref().unwrap().len()
+        let payload = payload.into();
+        let size_hint = payload.size_hint();
+        match size_hint {
+            Some(len) => self.set_content_length(len),
+            None => {
+                self.headers.remove(http::header::CONTENT_LENGTH);
+            }
+        }
+        self.payload = Some(payload);
+        size_hint
     }
-    pub fn unload(&mut self) -> Option<Box<[u8]>> {
+    pub fn unload(&mut self) -> Option<SignedRequestPayload> {
         self.payload.take()
     }
+    /// `None` if the body is a stream, which can only be sent once.
+    pub fn try_clone(&self) -> Option<Self> {
+        let payload = match self.payload {
+            Some(ref payload) => Some(payload.try_clone()?),
+            None => None,
+        };
+        Some(Self {
+            method: self.method.clone(),
+            region: self.region.clone(),
+            bucket: self.bucket.clone(),
+            object: self.object.clone(),
+            headers: self.headers.clone(),
+            params: self.params.clone(),
+            payload,
+            url: self.url.clone(),
+            schema: self.schema,
+            endpoint_type: self.endpoint_type,
+            addressing_style: self.addressing_style,
+            signature_version: self.signature_version,
+            additional_headers: self.additional_headers.clone(),
+            timeout: self.timeout,
+            read_timeout: self.read_timeout,
+            idempotent: self.idempotent,
+        })
+    }
     pub fn set_content_type<V>(&mut self, content_type: V)
     where
         V: Into<HeaderValue>,
@@ -105,6 +212,34 @@ impl SignedRequest {
     pub fn get_schema(&self) -> String {
         format!("{}", self.schema)
     }
+    pub fn set_endpoint_type(&mut self, endpoint_type: EndpointType) {
+        self.endpoint_type = endpoint_type
+    }
+    pub fn get_endpoint_type(&self) -> EndpointType {
+        self.endpoint_type
+    }
+    /// Whatever the style, the request is still signed as `/bucket/object`.
+    pub fn set_addressing_style(&mut self, addressing_style: AddressingStyle) {
+        self.addressing_style = addressing_style
+    }
+    pub fn get_addressing_style(&self) -> AddressingStyle {
+        self.addressing_style
+    }
+    pub fn set_signature_version(&mut self, signature_version: SignatureVersion) {
+        self.signature_version = signature_version
+    }
+    pub fn get_signature_version(&self) -> SignatureVersion {
+        self.signature_version
+    }
+    /// Headers other than `x-oss-*`, `content-type` and `content-md5` to be
+    /// covered by a V4 signature; ignored by V1.
+    pub fn set_additional_headers<I, S>(&mut self, headers: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.additional_headers = headers.into_iter().map(Into::into).collect();
+    }
     // /// Computes and sets the Content-MD5 header based on the current payload.
     // ///
     // /// Has no effect if the payload is not set, or is not a buffer. Will not
@@ -124,25 +259,61 @@ impl SignedRequest {
     }
 
     pub fn generate_url(&self) -> HttpResult<Url> {
-        let url_str = if self.bucket.is_empty() {
-            format!(
-                "{}://{}/{}{}",
+        let url_str = format!("{}{}", self.base_url(), get_params_str(&self.params));
+        Url::parse(&url_str).map_err(errors::url)
+    }
+
+    /// `schema://host/object` without any query string, the object key percent-encoded
+    pub(crate) fn base_url(&self) -> String {
+        let endpoint = self.region.endpoint_of(self.endpoint_type);
+        let object = encode_key(&self.object);
+        if self.bucket.is_empty() {
+            return format!("{}://{}/{}", self.get_schema(), endpoint, object);
+        }
+        match self.addressing_style {
+            AddressingStyle::VirtualHosted => format!(
+                "{}://{}.{}/{}",
                 self.get_schema(),
-                self.region.endpoint(),
-                self.object,
-                get_params_str(&self.params),
-            )
-        } else {
-            format!(
-                "{}://{}.{}/{}{}",
+                self.bucket,
+                endpoint,
+                object
+            ),
+            AddressingStyle::Path => format!(
+                "{}://{}/{}/{}",
                 self.get_schema(),
+                endpoint,
                 self.bucket,
-                self.region.endpoint(),
-                self.object,
-                get_params_str(&self.params),
-            )
-        };
-        Url::parse(&url_str).map_err(errors::url)
+                object
+            ),
+            AddressingStyle::Cname => format!("{}://{}/{}", self.get_schema(), endpoint, object),
+        }
+    }
+
+    /// Overrides the request timeout of the client for this request.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+    /// Overrides the read timeout of the client for this request.
+    pub fn set_read_timeout(&mut self, timeout: Duration) {
+        self.read_timeout = Some(timeout);
+    }
+    pub fn read_timeout(&self) -> Option<Duration> {
+        self.read_timeout
+    }
+    /// Whether sending the request twice does no harm, so that it may be sent
+    /// again after a network failure, when it may or may not have reached OSS.
+    /// Only PUT, GET, HEAD and DELETE requests are by default.
+    pub fn set_idempotent(&mut self, idempotent: bool) {
+        self.idempotent = Some(idempotent);
+    }
+    pub fn is_idempotent(&self) -> bool {
+        self.idempotent.unwrap_or(matches!(
+            self.method,
+            Method::PUT | Method::GET | Method::HEAD | Method::DELETE
+        ))
     }
 
     /// Set the signed request's method.
@@ -174,10 +345,10 @@ fn get_params_str(params: &Params) -> String {
         } else {
             result += "&";
         }
+        result += &encode_query(k);
         if let Some(_v) = v {
-            result += &format!("{}={}", k, _v);
-        } else {
-            result += k;
+            result += "=";
+            result += &encode_query(_v);
         }
     }
     result
@@ -193,10 +364,102 @@ mod tests {
             &Region::BeiJing,
             "dev-sheet-calced",
             "A",
-            "",
-            "",
             Schema::Http,
         );
         println!("{:?}", sr.generate_url());
     }
+    #[test]
+    fn internal_endpoint_url_test() {
+        let mut sr = SignedRequest::new(
+            Method::GET,
+            &Region::HangZhou,
+            "bucket",
+            "object",
+            Schema::Https,
+        );
+        sr.set_endpoint_type(EndpointType::Internal);
+        assert_eq!(
+            sr.generate_url().unwrap().as_str(),
+            "https://bucket.oss-cn-hangzhou-internal.aliyuncs.com/object"
+        );
+    }
+    #[test]
+    fn custom_endpoint_url_test() {
+        let mut sr = SignedRequest::new(
+            Method::GET,
+            &Region::custom("cn-private", "oss.example.com:9000"),
+            "bucket",
+            "object",
+            Schema::Http,
+        );
+        assert_eq!(
+            sr.generate_url().unwrap().as_str(),
+            "http://bucket.oss.example.com:9000/object"
+        );
+        sr.region = Region::custom("cn-hangzhou", "static.example.com");
+        sr.set_addressing_style(AddressingStyle::Cname);
+        assert_eq!(
+            sr.generate_url().unwrap().as_str(),
+            "http://static.example.com/object"
+        );
+    }
+    #[test]
+    fn path_style_url_test() {
+        let mut sr = SignedRequest::new(
+            Method::GET,
+            &Region::custom("cn-hangzhou", "127.0.0.1:9000"),
+            "bucket",
+            "dir/object",
+            Schema::Http,
+        );
+        sr.set_addressing_style(AddressingStyle::Path);
+        assert_eq!(
+            sr.generate_url().unwrap().as_str(),
+            "http://127.0.0.1:9000/bucket/dir/object"
+        );
+        sr.object = String::new();
+        sr.add_params("prefix", "dir/");
+        assert_eq!(
+            sr.generate_url().unwrap().as_str(),
+            "http://127.0.0.1:9000/bucket/?prefix=dir%2F"
+        );
+    }
+    #[test]
+    fn encoded_url_round_trip_test() {
+        use percent_encoding::percent_decode_str;
+        let keys = [
+            "dir/sub dir/file name.txt",
+            "a+b=c&d",
+            "100%real#fragment?query",
+            "中文/文件名.pdf",
+            "emoji😀",
+        ];
+        for style in [AddressingStyle::VirtualHosted, AddressingStyle::Path].iter() {
+            for key in keys.iter() {
+                let mut sr = SignedRequest::new(
+                    Method::GET,
+                    &Region::HangZhou,
+                    "bucket",
+                    *key,
+                    Schema::Https,
+                );
+                sr.set_addressing_style(*style);
+                sr.add_params("response-content-disposition", *key);
+                sr.add_params("acl", None);
+                let url = sr.generate_url().unwrap();
+                assert!(url.fragment().is_none());
+                let path = percent_decode_str(url.path()).decode_utf8().unwrap();
+                let expected = match style {
+                    AddressingStyle::Path => format!("/bucket/{}", key),
+                    _ => format!("/{}", key),
+                };
+                assert_eq!(path, expected);
+                let params: Vec<_> = url.query_pairs().collect();
+                assert_eq!(params.len(), 2);
+                assert_eq!(params[0].0, "acl");
+                assert_eq!(params[1].0, "response-content-disposition");
+                assert_eq!(params[1].1, *key);
+            }
+        }
+    }
 }
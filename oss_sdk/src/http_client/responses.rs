@@ -1,38 +1,106 @@
-use std::pin::Pin;
+use super::errors::{self, HttpResult};
+use super::*;
 
-use bytes::Bytes;
-use http::{HeaderMap, StatusCode};
+use std::{io, time::Duration};
+
+use bytes::{Bytes, BytesMut};
+use futures::{stream, Stream, StreamExt, TryStreamExt};
+use http::{HeaderMap, Method, StatusCode};
+
+/// Most `collect` reserves up front, however large the body claims to be.
+const MAX_PREALLOCATION: usize = 1024 * 1024;
 
 /// Stores the response from a HTTP request.
 pub struct HttpResponse {
     /// Status code of HTTP Request
     pub status: StatusCode,
-    /// Contents of Response
-    pub body: Pin<Box<Bytes>>,
+    /// Contents of Response, streamed as it is read
+    pub body: ByteStream,
     /// Response headers
     pub headers: HeaderMap,
 }
 
 impl std::fmt::Debug for HttpResponse {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // let mut content = String::new();
-        // self.body.read_to_string(&mut content).unwrap_or_default();
         f.debug_struct("HttpResponse")
             .field("StatusCode", &self.status.as_str())
-            .field("Content", &String::from_utf8(self.body.to_vec()))
+            .field("Content", &self.body)
             .field("Headers", &self.headers)
             .finish()
     }
 }
 impl HttpResponse {
-    pub(crate) async  fn from_resp(resp: reqwest::Response) -> Self {
+    /// Wraps `resp` to a `method` request, failing the body with a `TimedOut`
+    /// error when no data arrives for `read_timeout`.
+    pub(crate) fn from_resp(
+        resp: reqwest::Response,
+        method: &Method,
+        read_timeout: Option<Duration>,
+    ) -> Self {
         let status = resp.status();
         let headers = resp.headers().to_owned();
-        let bytes = resp.bytes().await.unwrap();
+        let stream = resp.bytes_stream().map_err(|e| {
+            if e.is_timeout() {
+                io::Error::new(io::ErrorKind::TimedOut, e)
+            } else {
+                io::Error::other(e)
+            }
+        });
+        let stream = match read_timeout {
+            Some(read_timeout) => idle_timeout(stream, read_timeout).boxed(),
+            None => stream.boxed(),
+        };
+        // The Content-Length of a HEAD response is the size of the object
+        let content_length = match *method {
+            Method::HEAD => None,
+            _ => headers
+                .get(http::header::CONTENT_LENGTH)
+                .and_then(|len| len.to_str().ok()?.parse().ok()),
+        };
+        let body = match content_length {
+            Some(len) => ByteStream::new_with_size(stream, len),
+            None => ByteStream::new(stream),
+        };
         Self {
             status,
             headers,
-            body: Box::pin(bytes),
+            body,
         }
     }
+    /// Value of the header `name`, if any and valid UTF-8.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+    /// `x-oss-request-id`, what OSS support asks for.
+    pub fn request_id(&self) -> &str {
+        self.header("x-oss-request-id").unwrap_or_default()
+    }
+    /// Reads the whole body in memory, only meant for small ones.
+    pub async fn collect(self) -> HttpResult<Bytes> {
+        let capacity = self.body.size_hint().unwrap_or_default();
+        let mut buf = BytesMut::with_capacity(capacity.min(MAX_PREALLOCATION));
+        let mut body = self.body;
+        while let Some(chunk) = body.try_next().await.map_err(errors::read)? {
+            buf.extend_from_slice(&chunk);
+        }
+        Ok(buf.freeze())
+    }
+}
+
+fn idle_timeout<S>(stream: S, timeout: Duration) -> impl Stream<Item = io::Result<Bytes>> + Send
+where
+    S: Stream<Item = io::Result<Bytes>> + Send + 'static,
+{
+    stream::unfold(Box::pin(stream), move |mut stream| async move {
+        match tokio::time::timeout(timeout, stream.next()).await {
+            Ok(chunk) => Some((chunk?, stream)),
+            Err(_) => {
+                let e = io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("no data received for {:?}", timeout),
+                );
+                Some((Err(e), stream))
+            }
+        }
+    })
 }
@@ -1,19 +1,22 @@
-use std::time::Duration;
-
 use super::{
     errors::{self, HttpError, HttpResult},
     responses::HttpResponse,
-    SignedRequest,
+    SignedRequest, SignedRequestPayload,
 };
 
+use crate::{CredentialsProvider, Timeouts};
 use async_trait::async_trait;
 
 #[async_trait]
 pub trait SignAndDispatch {
+    /// Signs `request` with the current credentials of `credentials` and sends it,
+    /// within the request and read timeouts of `timeouts`. The connect timeout is
+    /// left to the client, which usually only takes one when it is built.
     async fn sign_and_dispatch(
         &self,
         mut request: SignedRequest,
-        timeout: Option<Duration>,
+        credentials: &dyn CredentialsProvider,
+        timeouts: &Timeouts,
     ) -> HttpResult<HttpResponse>;
 }
 
@@ -22,31 +25,45 @@ impl SignAndDispatch for reqwest::Client {
     async fn sign_and_dispatch(
         &self,
         rqst: SignedRequest,
-        timeout: Option<Duration>,
+        credentials: &dyn CredentialsProvider,
+        timeouts: &Timeouts,
     ) -> Result<HttpResponse, HttpError> {
         let mut rqst = rqst;
-        rqst.oss_sign()?;
+        let credentials = credentials
+            .credentials()
+            .await
+            .map_err(errors::credentials)?;
+        rqst.oss_sign(&credentials)?;
         let method = rqst.method().to_owned();
-        let url = rqst.get_url().unwrap().to_owned();
+        let url = rqst.generate_url()?;
         let headers = rqst.headers().to_owned();
-        let mut request_builder = self
-            .request(method, url)
-            .headers(headers)
-            .query(rqst.params());
-        if let Some(_duration) = timeout {
+        let mut request_builder = self.request(method.clone(), url).headers(headers);
+        if let Some(_duration) = timeouts.request {
             request_builder = request_builder.timeout(_duration);
         }
-        if let Some(_payload) = rqst.payload {
-            request_builder = request_builder.body(_payload.into_vec());
+        match rqst.payload {
+            Some(SignedRequestPayload::Buffer(buf)) => request_builder = request_builder.body(buf),
+            Some(SignedRequestPayload::Stream(stream)) => {
+                request_builder = request_builder.body(reqwest::Body::wrap_stream(stream))
+            }
+            None => {}
         }
         let ret = request_builder.send().await?;
-        let http_resp = HttpResponse::from_resp(ret).await;
+        let http_resp = HttpResponse::from_resp(ret, &method, timeouts.read);
         Ok(http_resp)
     }
 }
 impl From<reqwest::Error> for HttpError {
     fn from(e: reqwest::Error) -> Self {
-        errors::client(e)
+        if e.is_timeout() {
+            errors::timeout(e)
+        } else if e.is_body() {
+            errors::body(e)
+        } else if e.is_connect() || e.is_request() {
+            errors::client(e)
+        } else {
+            errors::other(e)
+        }
     }
 }
 
@@ -1,15 +1,37 @@
 #[macro_use]
 extern crate derive_more;
 
+mod bucket;
+mod builder;
+mod checkpoint;
+mod config;
+mod credentials;
+mod download;
 mod http_client;
+mod list_objects;
+mod multipart;
+mod object;
 mod oss;
 mod statics;
+#[cfg(test)]
+mod test_util;
 mod types;
+mod upload;
+mod xml;
 
+pub use credentials::{
+    AssumeRoleProvider, AutoRefreshingProvider, ChainProvider, Credentials, CredentialsError,
+    CredentialsProvider, EcsRamRoleProvider, EnvironmentProvider, OidcRoleProvider,
+    ProfileProvider, StaticProvider, STS_ENDPOINT,
+};
 pub use statics::OSS_PREFIX;
 pub use types::*;
 
-pub use crate::http_client::{HttpError, HttpResponse, SignAndDispatch};
+pub use crate::builder::OSSClientBuilder;
+pub use crate::config::OssConfig;
+pub use crate::http_client::{
+    HttpError, HttpResponse, SignAndDispatch, SignedRequest, SignedRequestPayload,
+};
 pub use crate::oss::OSSClient;
 
 pub type OssClient = OSSClient<reqwest::Client>;
@@ -0,0 +1,196 @@
+use futures::{stream, Stream, TryStreamExt};
+
+use crate::{
+    http_client::{encoding::decode, SignAndDispatch},
+    xml, Error, ListObjectsV2Options, ListObjectsV2Output, OSSClient, ObjectSummary, Result,
+};
+
+impl<C: SignAndDispatch + Send + Sync> OSSClient<C> {
+    /// One page of the objects of the bucket, see `list_objects_v2_stream`
+    /// for all of them.
+    pub async fn list_objects_v2(
+        &self,
+        options: ListObjectsV2Options,
+    ) -> Result<ListObjectsV2Output> {
+        let mut rqst = self.get_request(None);
+        rqst.add_params("list-type", "2");
+        rqst.add_params("encoding-type", "url");
+        let params = [
+            ("prefix", &options.prefix),
+            ("delimiter", &options.delimiter),
+            ("start-after", &options.start_after),
+            ("continuation-token", &options.continuation_token),
+        ];
+        for (k, v) in params.iter() {
+            if let Some(v) = v {
+                rqst.add_params(*k, v.as_str());
+            }
+        }
+        if let Some(max_keys) = options.max_keys {
+            rqst.add_params("max-keys", max_keys.to_string().as_str());
+        }
+        if options.fetch_owner {
+            rqst.add_params("fetch-owner", "true");
+        }
+
+        let mut output: ListObjectsV2Output = xml::from_response(self.send(rqst).await?).await?;
+        if output.encoding_type == "url" {
+            output.prefix = decode(&output.prefix);
+            output.delimiter = decode(&output.delimiter);
+            output.start_after = decode(&output.start_after);
+            for object in output.contents.iter_mut() {
+                object.key = decode(&object.key);
+            }
+            for common_prefix in output.common_prefixes.iter_mut() {
+                common_prefix.prefix = decode(&common_prefix.prefix);
+            }
+        }
+        Ok(output)
+    }
+
+    /// Every object matching `options`, fetching the pages as it goes. The
+    /// common prefixes of a delimited listing are left out. A truncated page
+    /// whose continuation token does not advance ends it with an error.
+    pub fn list_objects_v2_stream(
+        &self,
+        options: ListObjectsV2Options,
+    ) -> impl Stream<Item = Result<ObjectSummary>> + '_ {
+        stream::try_unfold(Some(options), move |options| async move {
+            let options = match options {
+                Some(options) => options,
+                None => return Ok::<_, Error>(None),
+            };
+            let page = self.list_objects_v2(options.clone()).await?;
+            let next = if page.is_truncated {
+                let token = options.continuation_token.as_deref().unwrap_or_default();
+                if page.next_continuation_token.is_empty() || page.next_continuation_token == token
+                {
+                    return Err(Error::stuck_listing(token));
+                }
+                Some(ListObjectsV2Options {
+                    continuation_token: Some(page.next_continuation_token),
+                    ..options
+                })
+            } else {
+                None
+            };
+            Ok(Some((
+                stream::iter(page.contents.into_iter().map(Ok)),
+                next,
+            )))
+        })
+        .try_flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_util::{mock_client, MockRequest, MockResponse, MockServer},
+        ObjectType, StorageClass,
+    };
+
+    fn object(key: &str) -> String {
+        format!(
+            "<Contents><Key>{}</Key><LastModified>2019-10-12T16:21:22.000Z</LastModified>\
+             <ETag>\"4F7A3B2C\"</ETag><Type>Normal</Type><Size>344606</Size>\
+             <StorageClass>Standard</StorageClass>\
+             <Owner><ID>0022012****</ID><DisplayName>user-example</DisplayName></Owner>\
+             </Contents>",
+            key
+        )
+    }
+
+    fn list_server(rqst: &MockRequest) -> MockResponse {
+        if rqst.param("list-type").as_deref() != Some("2")
+            || rqst.param("encoding-type").as_deref() != Some("url")
+        {
+            return MockResponse::status(400);
+        }
+        let (contents, truncated) = match rqst.param("continuation-token").as_deref() {
+            None => (object("dir/a%20b.txt") + &object("dir/%E4%B8%AD"), true),
+            Some("token") => (object("dir/c"), false),
+            Some(_) => return MockResponse::status(400),
+        };
+        MockResponse::ok(format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+             <ListBucketResult><Name>bucket</Name><Prefix>dir%2F</Prefix>\
+             <MaxKeys>2</MaxKeys><Delimiter>%2F</Delimiter><EncodingType>url</EncodingType>\
+             <IsTruncated>{}</IsTruncated><NextContinuationToken>token</NextContinuationToken>\
+             <KeyCount>3</KeyCount>{}<CommonPrefixes><Prefix>dir/sub%20dir/</Prefix></CommonPrefixes>\
+             </ListBucketResult>",
+            truncated, contents
+        ))
+    }
+
+    #[tokio::test]
+    async fn list_objects_v2_test() {
+        let server = MockServer::start(list_server).await;
+        let oss_instance = mock_client(&server);
+
+        let page = oss_instance
+            .list_objects_v2(ListObjectsV2Options {
+                prefix: Some("dir/".to_owned()),
+                delimiter: Some("/".to_owned()),
+                max_keys: Some(2),
+                fetch_owner: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.name, "bucket");
+        assert_eq!(page.prefix, "dir/");
+        assert_eq!(page.delimiter, "/");
+        assert!(page.is_truncated);
+        assert_eq!(page.next_continuation_token, "token");
+        let object = &page.contents[0];
+        assert_eq!(object.key, "dir/a b.txt");
+        assert_eq!(object.etag, "\"4F7A3B2C\"");
+        assert_eq!(object.size, 344606);
+        assert_eq!(object.object_type, Some(ObjectType::Normal));
+        assert_eq!(object.storage_class, Some(StorageClass::Standard));
+        assert_eq!(object.owner.as_ref().unwrap().display_name, "user-example");
+        assert_eq!(page.contents[1].key, "dir/中");
+        assert_eq!(page.common_prefixes[0].prefix, "dir/sub dir/");
+
+        let rqst = &server.requests()[0];
+        assert_eq!(rqst.path, "/bucket/");
+        assert_eq!(rqst.param("prefix").as_deref(), Some("dir/"));
+        assert_eq!(rqst.param("delimiter").as_deref(), Some("/"));
+        assert_eq!(rqst.param("max-keys").as_deref(), Some("2"));
+        assert_eq!(rqst.param("fetch-owner").as_deref(), Some("true"));
+    }
+
+    #[tokio::test]
+    async fn list_objects_v2_stream_test() {
+        let server = MockServer::start(list_server).await;
+        let oss_instance = mock_client(&server);
+
+        let keys: Vec<String> = oss_instance
+            .list_objects_v2_stream(ListObjectsV2Options::default())
+            .map_ok(|object| object.key)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(keys, vec!["dir/a b.txt", "dir/中", "dir/c"]);
+        assert_eq!(server.requests().len(), 2);
+
+        // A truncated page without a continuation token
+        let server = MockServer::start(|_| {
+            MockResponse::ok(
+                "<ListBucketResult><Name>bucket</Name><IsTruncated>true</IsTruncated>\
+                 </ListBucketResult>",
+            )
+        })
+        .await;
+        let oss_instance = mock_client(&server);
+        let err = oss_instance
+            .list_objects_v2_stream(ListObjectsV2Options::default())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("advance past"), "{}", err);
+        assert_eq!(server.requests().len(), 1);
+    }
+}
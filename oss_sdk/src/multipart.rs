@@ -0,0 +1,611 @@
+use futures::{stream, Stream, TryStreamExt};
+use http::{header::HeaderName, HeaderValue};
+use serde::Serialize;
+
+use crate::{
+    http_client::{encoding::encode_key, SignAndDispatch, SignedRequestPayload},
+    object::{add_put_headers, crc64},
+    xml, CompleteMultipartUploadOptions, CompleteMultipartUploadOutput,
+    CompleteMultipartUploadResult, CompletedPart, CopyPartResult, CopySource, Error,
+    InitiateMultipartUploadOutput, ListMultipartUploadsOptions, ListMultipartUploadsOutput,
+    ListPartsOptions, ListPartsOutput, MultipartUpload, OSSClient, PartSummary, PutObjectOptions,
+    Result, UploadPartOutput,
+};
+
+#[derive(Serialize)]
+struct CompleteMultipartUpload<'a> {
+    #[serde(rename = "Part")]
+    parts: &'a [CompletedPart],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CallbackParam<'a> {
+    callback_url: &'a str,
+    callback_body: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    callback_body_type: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    callback_host: Option<&'a str>,
+}
+
+impl<C: SignAndDispatch + Send + Sync> OSSClient<C> {
+    /// Starts a multipart upload of `key`. `options` are the headers the
+    /// assembled object gets, as for `put_object`.
+    pub async fn initiate_multipart_upload<K: Into<String>>(
+        &self,
+        key: K,
+        options: PutObjectOptions,
+    ) -> Result<InitiateMultipartUploadOutput> {
+        let mut rqst = self.post_request(key, Vec::new());
+        rqst.add_params("uploads", None);
+        add_put_headers(&mut rqst, &options)?;
+        xml::from_response(self.send(rqst).await?).await
+    }
+
+    /// Uploads part `part_number` (1 to 10000) of `body`, which must be at
+    /// least 100 KB but for the last part.
+    pub async fn upload_part<K, B>(
+        &self,
+        key: K,
+        upload_id: &str,
+        part_number: u32,
+        body: B,
+    ) -> Result<UploadPartOutput>
+    where
+        K: Into<String>,
+        B: Into<SignedRequestPayload>,
+    {
+        let mut rqst = self.put_request(key, body);
+        rqst.add_params("partNumber", part_number.to_string().as_str());
+        rqst.add_params("uploadId", upload_id);
+        let resp = self.send(rqst).await?;
+        Ok(UploadPartOutput {
+            part_number,
+            etag: resp.header("etag").unwrap_or_default().to_owned(),
+            crc64: crc64(&resp),
+            request_id: resp.request_id().to_owned(),
+        })
+    }
+
+    /// Copies `source`, or the `range` of it (both ends inclusive), as part
+    /// `part_number`.
+    pub async fn upload_part_copy<K: Into<String>>(
+        &self,
+        key: K,
+        upload_id: &str,
+        part_number: u32,
+        source: &CopySource,
+        range: Option<(u64, u64)>,
+    ) -> Result<UploadPartOutput> {
+        let mut rqst = self.put_request(key, Vec::new());
+        rqst.add_params("partNumber", part_number.to_string().as_str());
+        rqst.add_params("uploadId", upload_id);
+        let mut copy_source = format!("/{}/{}", source.bucket, encode_key(&source.key));
+        if let Some(ref version_id) = source.version_id {
+            copy_source = format!("{}?versionId={}", copy_source, version_id);
+        }
+        rqst.add_header(
+            HeaderName::from_static("x-oss-copy-source"),
+            HeaderValue::from_str(&copy_source)?,
+        );
+        if let Some((first, last)) = range {
+            rqst.add_header(
+                HeaderName::from_static("x-oss-copy-source-range"),
+                HeaderValue::from_str(&format!("bytes={}-{}", first, last))?,
+            );
+        }
+        let resp = self.send(rqst).await?;
+        let crc64 = crc64(&resp);
+        let request_id = resp.request_id().to_owned();
+        let result: CopyPartResult = xml::from_response(resp).await?;
+        Ok(UploadPartOutput {
+            part_number,
+            etag: result.etag,
+            crc64,
+            request_id,
+        })
+    }
+
+    /// Assembles `parts` into the object, in the order of their part numbers
+    /// whatever the order they are given in.
+    pub async fn complete_multipart_upload<K: Into<String>>(
+        &self,
+        key: K,
+        upload_id: &str,
+        parts: &[CompletedPart],
+        options: CompleteMultipartUploadOptions,
+    ) -> Result<CompleteMultipartUploadOutput> {
+        let key = key.into();
+        // OSS rejects parts out of order with InvalidPartOrder
+        let mut parts = parts.to_vec();
+        parts.sort_by_key(|part| part.part_number);
+        let body = xml::to_string(&CompleteMultipartUpload { parts: &parts })?;
+        let mut rqst = self.post_request(key.as_str(), body);
+        rqst.add_params("uploadId", upload_id);
+        if options.forbid_overwrite {
+            rqst.add_header(
+                HeaderName::from_static("x-oss-forbid-overwrite"),
+                HeaderValue::from_static("true"),
+            );
+        }
+        if let Some(ref callback) = options.callback {
+            let param = serde_json::to_vec(&CallbackParam {
+                callback_url: &callback.url,
+                callback_body: &callback.body,
+                callback_body_type: callback.body_type.as_deref(),
+                callback_host: callback.host.as_deref(),
+            })
+            .expect("callback params are valid JSON");
+            rqst.add_header(
+                HeaderName::from_static("x-oss-callback"),
+                HeaderValue::from_str(&base64::encode(param))?,
+            );
+            if !callback.vars.is_empty() {
+                let vars =
+                    serde_json::to_vec(&callback.vars).expect("callback vars are valid JSON");
+                rqst.add_header(
+                    HeaderName::from_static("x-oss-callback-var"),
+                    HeaderValue::from_str(&base64::encode(vars))?,
+                );
+            }
+        }
+
+        let resp = self.send(rqst).await?;
+        let mut output = CompleteMultipartUploadOutput {
+            bucket: self.get_bucket().to_owned(),
+            key,
+            etag: resp.header("etag").unwrap_or_default().to_owned(),
+            crc64: crc64(&resp),
+            version_id: resp.header("x-oss-version-id").map(str::to_owned),
+            request_id: resp.request_id().to_owned(),
+            ..Default::default()
+        };
+        // With a callback, the body is whatever the callback server answered.
+        if options.callback.is_some() {
+            output.callback_result = Some(resp.collect().await?);
+        } else {
+            let result: CompleteMultipartUploadResult = xml::from_response(resp).await?;
+            output.bucket = result.bucket;
+            output.key = result.key;
+            output.etag = result.etag;
+            output.location = result.location;
+        }
+        Ok(output)
+    }
+
+    /// Cancels the upload and deletes the parts uploaded so far.
+    pub async fn abort_multipart_upload<K: Into<String>>(
+        &self,
+        key: K,
+        upload_id: &str,
+    ) -> Result<()> {
+        let mut rqst = self.del_request(key);
+        rqst.add_params("uploadId", upload_id);
+        self.send(rqst).await?;
+        Ok(())
+    }
+
+    /// One page of the parts uploaded so far, see `list_parts_stream` for all of them.
+    pub async fn list_parts<K: Into<String>>(
+        &self,
+        key: K,
+        upload_id: &str,
+        options: ListPartsOptions,
+    ) -> Result<ListPartsOutput> {
+        let mut rqst = self.get_request(key.into().as_str());
+        rqst.add_params("uploadId", upload_id);
+        if let Some(max_parts) = options.max_parts {
+            rqst.add_params("max-parts", max_parts.to_string().as_str());
+        }
+        if let Some(marker) = options.part_number_marker {
+            rqst.add_params("part-number-marker", marker.to_string().as_str());
+        }
+        xml::from_response(self.send(rqst).await?).await
+    }
+
+    /// Every part uploaded so far, fetching the pages as it goes. A truncated
+    /// page whose next marker does not advance ends it with an error.
+    pub fn list_parts_stream<'a>(
+        &'a self,
+        key: &'a str,
+        upload_id: &'a str,
+    ) -> impl Stream<Item = Result<PartSummary>> + 'a {
+        stream::try_unfold(
+            Some(ListPartsOptions::default()),
+            move |options| async move {
+                let options = match options {
+                    Some(options) => options,
+                    None => return Ok::<_, Error>(None),
+                };
+                let page = self.list_parts(key, upload_id, options.clone()).await?;
+                let next = if page.is_truncated {
+                    let marker = options.part_number_marker.unwrap_or_default();
+                    if page.next_part_number_marker <= marker {
+                        return Err(Error::stuck_listing(marker));
+                    }
+                    Some(ListPartsOptions {
+                        part_number_marker: Some(page.next_part_number_marker),
+                        ..options
+                    })
+                } else {
+                    None
+                };
+                Ok(Some((stream::iter(page.parts.into_iter().map(Ok)), next)))
+            },
+        )
+        .try_flatten()
+    }
+
+    /// One page of the uploads initiated in the bucket and neither completed
+    /// nor aborted, see `list_multipart_uploads_stream` for all of them.
+    pub async fn list_multipart_uploads(
+        &self,
+        options: ListMultipartUploadsOptions,
+    ) -> Result<ListMultipartUploadsOutput> {
+        let mut rqst = self.get_request(None);
+        rqst.add_params("uploads", None);
+        let params = [
+            ("prefix", &options.prefix),
+            ("delimiter", &options.delimiter),
+            ("key-marker", &options.key_marker),
+            ("upload-id-marker", &options.upload_id_marker),
+        ];
+        for (k, v) in params.iter() {
+            if let Some(v) = v {
+                rqst.add_params(*k, v.as_str());
+            }
+        }
+        if let Some(max_uploads) = options.max_uploads {
+            rqst.add_params("max-uploads", max_uploads.to_string().as_str());
+        }
+        xml::from_response(self.send(rqst).await?).await
+    }
+
+    /// Every upload matching `options`, fetching the pages as it goes. The
+    /// common prefixes of a delimited listing are left out. A truncated page
+    /// whose next markers do not advance ends it with an error.
+    pub fn list_multipart_uploads_stream(
+        &self,
+        options: ListMultipartUploadsOptions,
+    ) -> impl Stream<Item = Result<MultipartUpload>> + '_ {
+        stream::try_unfold(Some(options), move |options| async move {
+            let options = match options {
+                Some(options) => options,
+                None => return Ok::<_, Error>(None),
+            };
+            let page = self.list_multipart_uploads(options.clone()).await?;
+            let next = if page.is_truncated {
+                let marker = (
+                    options.key_marker.as_deref().unwrap_or_default(),
+                    options.upload_id_marker.as_deref().unwrap_or_default(),
+                );
+                let next = (
+                    page.next_key_marker.as_str(),
+                    page.next_upload_id_marker.as_str(),
+                );
+                if next.0.is_empty() || next == marker {
+                    return Err(Error::stuck_listing(marker));
+                }
+                Some(ListMultipartUploadsOptions {
+                    key_marker: Some(page.next_key_marker),
+                    upload_id_marker: Some(page.next_upload_id_marker),
+                    ..options
+                })
+            } else {
+                None
+            };
+            Ok(Some((stream::iter(page.uploads.into_iter().map(Ok)), next)))
+        })
+        .try_flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_util::{mock_client, MockRequest, MockResponse, MockServer},
+        Callback,
+    };
+    use futures::TryStreamExt;
+
+    fn multipart_server(rqst: &MockRequest) -> MockResponse {
+        match (rqst.method.as_str(), rqst.query.as_str()) {
+            ("POST", "uploads") => MockResponse::ok(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+                <InitiateMultipartUploadResult>
+                    <Bucket>bucket</Bucket>
+                    <Key>big.bin</Key>
+                    <UploadId>0004B9894A22E5B1888A1E29F823****</UploadId>
+                </InitiateMultipartUploadResult>"#,
+            ),
+            ("PUT", _) if rqst.header("x-oss-copy-source").is_some() => MockResponse::ok(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+                <CopyPartResult>
+                    <LastModified>2014-07-17T06:27:54.000Z</LastModified>
+                    <ETag>"5B3C1A2E053D763E1B002CC607C5A0FE"</ETag>
+                </CopyPartResult>"#,
+            ),
+            ("PUT", _) => MockResponse::ok("")
+                .header(
+                    "etag",
+                    format!("\"etag-{}\"", rqst.param("partNumber").unwrap()),
+                )
+                .header("x-oss-hash-crc64ecma", "42"),
+            ("POST", _) if rqst.header("x-oss-callback").is_some() => {
+                MockResponse::ok(r#"{"Status":"OK"}"#)
+            }
+            ("POST", _) => MockResponse::ok(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+                <CompleteMultipartUploadResult>
+                    <Location>http://bucket.oss-cn-hangzhou.aliyuncs.com/big.bin</Location>
+                    <Bucket>bucket</Bucket>
+                    <Key>big.bin</Key>
+                    <ETag>"097DE458AD02B5F89F9D0530231876****"</ETag>
+                </CompleteMultipartUploadResult>"#,
+            )
+            .header("x-oss-hash-crc64ecma", "1234"),
+            ("DELETE", _) => MockResponse::status(204),
+            ("GET", _) if rqst.param("uploads").is_some() => list_uploads_page(rqst),
+            ("GET", _) => list_parts_page(rqst),
+            _ => MockResponse::status(400),
+        }
+    }
+
+    fn list_parts_page(rqst: &MockRequest) -> MockResponse {
+        let (parts, truncated, next) = match rqst.param("part-number-marker").as_deref() {
+            None => (1..=2, true, 2),
+            Some("2") => (3..=3, false, 3),
+            Some(_) => return MockResponse::status(400),
+        };
+        let parts: String = parts
+            .map(|n| {
+                format!(
+                    "<Part><PartNumber>{}</PartNumber>\
+                     <LastModified>2012-02-23T07:01:34.000Z</LastModified>\
+                     <ETag>\"etag-{}\"</ETag><HashCrc64ecma>{}</HashCrc64ecma>\
+                     <Size>6291456</Size></Part>",
+                    n, n, n
+                )
+            })
+            .collect();
+        MockResponse::ok(format!(
+            "<ListPartsResult><Bucket>bucket</Bucket><Key>big.bin</Key>\
+             <UploadId>id</UploadId><PartNumberMarker>0</PartNumberMarker>\
+             <NextPartNumberMarker>{}</NextPartNumberMarker><MaxParts>2</MaxParts>\
+             <IsTruncated>{}</IsTruncated>{}</ListPartsResult>",
+            next, truncated, parts
+        ))
+    }
+
+    fn list_uploads_page(rqst: &MockRequest) -> MockResponse {
+        let (uploads, truncated) = match rqst.param("key-marker").as_deref() {
+            None => (vec![("a", "id-a")], true),
+            Some("a") if rqst.param("upload-id-marker").as_deref() == Some("id-a") => {
+                (vec![("b", "id-b")], false)
+            }
+            Some(_) => return MockResponse::status(400),
+        };
+        let uploads: String = uploads
+            .iter()
+            .map(|(key, id)| {
+                format!(
+                    "<Upload><Key>{}</Key><UploadId>{}</UploadId>\
+                     <StorageClass>Standard</StorageClass>\
+                     <Initiated>2012-02-23T04:18:23.000Z</Initiated></Upload>",
+                    key, id
+                )
+            })
+            .collect();
+        MockResponse::ok(format!(
+            "<ListMultipartUploadsResult><Bucket>bucket</Bucket><KeyMarker></KeyMarker>\
+             <UploadIdMarker></UploadIdMarker><NextKeyMarker>a</NextKeyMarker>\
+             <NextUploadIdMarker>id-a</NextUploadIdMarker><Delimiter>/</Delimiter>\
+             <Prefix></Prefix><MaxUploads>1</MaxUploads><IsTruncated>{}</IsTruncated>\
+             {}<CommonPrefixes><Prefix>dir/</Prefix></CommonPrefixes>\
+             </ListMultipartUploadsResult>",
+            truncated, uploads
+        ))
+    }
+
+    #[tokio::test]
+    async fn multipart_upload_test() {
+        let server = MockServer::start(multipart_server).await;
+        let oss_instance = mock_client(&server);
+
+        let initiated = oss_instance
+            .initiate_multipart_upload("big.bin", PutObjectOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(initiated.key, "big.bin");
+        let upload_id = initiated.upload_id.as_str();
+        assert_eq!(upload_id, "0004B9894A22E5B1888A1E29F823****");
+
+        let part1 = oss_instance
+            .upload_part("big.bin", upload_id, 1, vec![1u8; 1024])
+            .await
+            .unwrap();
+        assert_eq!(part1.etag, "\"etag-1\"");
+        assert_eq!(part1.crc64, Some(42));
+        let part2 = oss_instance
+            .upload_part_copy(
+                "big.bin",
+                upload_id,
+                2,
+                &CopySource::new("src-bucket", "dir/a b.bin"),
+                Some((0, 99)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(part2.etag, "\"5B3C1A2E053D763E1B002CC607C5A0FE\"");
+
+        // Given out of order, sent sorted
+        let parts: Vec<CompletedPart> = [&part2, &part1].iter().map(|p| (*p).into()).collect();
+        let completed = oss_instance
+            .complete_multipart_upload(
+                "big.bin",
+                upload_id,
+                &parts,
+                CompleteMultipartUploadOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(completed.etag, "\"097DE458AD02B5F89F9D0530231876****\"");
+        assert_eq!(
+            completed.location,
+            "http://bucket.oss-cn-hangzhou.aliyuncs.com/big.bin"
+        );
+        assert_eq!(completed.crc64, Some(1234));
+        assert_eq!(completed.callback_result, None);
+
+        let mut callback = Callback {
+            url: "http://example.com/callback".to_owned(),
+            body: "object=${object}&var=${x:var}".to_owned(),
+            ..Default::default()
+        };
+        callback.vars.insert("x:var".to_owned(), "value".to_owned());
+        let completed = oss_instance
+            .complete_multipart_upload(
+                "big.bin",
+                upload_id,
+                &parts,
+                CompleteMultipartUploadOptions {
+                    callback: Some(callback),
+                    forbid_overwrite: true,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            completed.callback_result.as_deref(),
+            Some(&br#"{"Status":"OK"}"#[..])
+        );
+        assert_eq!(completed.key, "big.bin");
+
+        oss_instance
+            .abort_multipart_upload("big.bin", upload_id)
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/bucket/big.bin");
+        assert_eq!(requests[1].param("partNumber").as_deref(), Some("1"));
+        assert_eq!(requests[1].param("uploadId").as_deref(), Some(upload_id));
+        assert_eq!(requests[1].body, vec![1u8; 1024]);
+        assert_eq!(
+            requests[2].header("x-oss-copy-source"),
+            Some("/src-bucket/dir/a%20b.bin")
+        );
+        assert_eq!(
+            requests[2].header("x-oss-copy-source-range"),
+            Some("bytes=0-99")
+        );
+        assert_eq!(
+            String::from_utf8(requests[3].body.clone()).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+             <CompleteMultipartUpload>\
+             <Part><PartNumber>1</PartNumber><ETag>&quot;etag-1&quot;</ETag></Part>\
+             <Part><PartNumber>2</PartNumber><ETag>&quot;5B3C1A2E053D763E1B002CC607C5A0FE&quot;</ETag></Part>\
+             </CompleteMultipartUpload>"
+        );
+        let callback = base64::decode(requests[4].header("x-oss-callback").unwrap()).unwrap();
+        assert_eq!(
+            String::from_utf8(callback).unwrap(),
+            r#"{"callbackUrl":"http://example.com/callback","callbackBody":"object=${object}&var=${x:var}"}"#
+        );
+        let vars = base64::decode(requests[4].header("x-oss-callback-var").unwrap()).unwrap();
+        assert_eq!(String::from_utf8(vars).unwrap(), r#"{"x:var":"value"}"#);
+        assert_eq!(requests[4].header("x-oss-forbid-overwrite"), Some("true"));
+        assert_eq!(requests[5].method, "DELETE");
+        assert_eq!(requests[5].param("uploadId").as_deref(), Some(upload_id));
+    }
+
+    #[tokio::test]
+    async fn list_parts_stream_test() {
+        let server = MockServer::start(multipart_server).await;
+        let oss_instance = mock_client(&server);
+
+        let page = oss_instance
+            .list_parts("big.bin", "id", ListPartsOptions::default())
+            .await
+            .unwrap();
+        assert!(page.is_truncated);
+        assert_eq!(page.next_part_number_marker, 2);
+        assert_eq!(page.parts[1].etag, "\"etag-2\"");
+        assert_eq!(page.parts[1].crc64, Some(2));
+        assert_eq!(page.parts[1].size, 6291456);
+
+        let parts: Vec<PartSummary> = oss_instance
+            .list_parts_stream("big.bin", "id")
+            .try_collect()
+            .await
+            .unwrap();
+        let numbers: Vec<u32> = parts.iter().map(|part| part.part_number).collect();
+        assert_eq!(numbers, vec![1, 2, 3]);
+
+        // Every page is truncated, and the marker stops at 2
+        let server = MockServer::start(|_| {
+            MockResponse::ok(
+                "<ListPartsResult><Bucket>bucket</Bucket><Key>big.bin</Key>\
+                 <UploadId>id</UploadId><NextPartNumberMarker>2</NextPartNumberMarker>\
+                 <IsTruncated>true</IsTruncated></ListPartsResult>",
+            )
+        })
+        .await;
+        let oss_instance = mock_client(&server);
+        let err = oss_instance
+            .list_parts_stream("big.bin", "id")
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("advance past 2"), "{}", err);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn list_multipart_uploads_stream_test() {
+        let server = MockServer::start(multipart_server).await;
+        let oss_instance = mock_client(&server);
+
+        let page = oss_instance
+            .list_multipart_uploads(ListMultipartUploadsOptions {
+                delimiter: Some("/".to_owned()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.uploads[0].upload_id, "id-a");
+        assert_eq!(page.common_prefixes[0].prefix, "dir/");
+        assert_eq!(server.requests()[0].path, "/bucket/");
+        assert_eq!(
+            server.requests()[0].param("delimiter").as_deref(),
+            Some("/")
+        );
+
+        let uploads: Vec<MultipartUpload> = oss_instance
+            .list_multipart_uploads_stream(ListMultipartUploadsOptions::default())
+            .try_collect()
+            .await
+            .unwrap();
+        let keys: Vec<&str> = uploads.iter().map(|upload| upload.key.as_str()).collect();
+        assert_eq!(keys, vec!["a", "b"]);
+
+        // A truncated page without next markers
+        let server = MockServer::start(|_| {
+            MockResponse::ok(
+                "<ListMultipartUploadsResult><Bucket>bucket</Bucket><IsTruncated>true</IsTruncated>\
+                 </ListMultipartUploadsResult>",
+            )
+        })
+        .await;
+        let oss_instance = mock_client(&server);
+        let err = oss_instance
+            .list_multipart_uploads_stream(ListMultipartUploadsOptions::default())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("advance past"), "{}", err);
+        assert_eq!(server.requests().len(), 1);
+    }
+}
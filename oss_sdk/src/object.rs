@@ -1,2 +1,678 @@
-use super::*;
+use std::{collections::BTreeMap, path::Path};
 
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use http::{header, header::HeaderName, HeaderValue};
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+};
+
+use crate::{
+    http_client::{
+        encoding::encode_query, errors, HttpResponse, SignAndDispatch, SignedRequest,
+        SignedRequestPayload,
+    },
+    ByteStream, ContentRange, GetObjectOutput, GetObjectRequest, OSSClient, ObjectMeta,
+    PutObjectOptions, PutObjectOutput, RestoreStatus, Result, ServerSideEncryption, OSS_PREFIX,
+};
+
+pub(crate) const HTTP_DATE: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+impl<C: SignAndDispatch + Send + Sync> OSSClient<C> {
+    /// Uploads `body` as `key` in a single request, which OSS limits to 5 GB.
+    /// `body` is either a buffer or a `ByteStream`, see `put_object_from_file`
+    /// and `put_object_from_reader`.
+    pub async fn put_object<K, B>(
+        &self,
+        key: K,
+        body: B,
+        options: PutObjectOptions,
+    ) -> Result<PutObjectOutput>
+    where
+        K: Into<String>,
+        B: Into<SignedRequestPayload>,
+    {
+        let mut rqst = self.put_request(key, body);
+        add_put_headers(&mut rqst, &options)?;
+        let resp = self.send(rqst).await?;
+        Ok(PutObjectOutput {
+            etag: resp.header("etag").unwrap_or_default().to_owned(),
+            crc64: crc64(&resp),
+            version_id: resp.header("x-oss-version-id").map(str::to_owned),
+            request_id: resp.request_id().to_owned(),
+        })
+    }
+
+    /// Streams `file`, from its current position, to `key` without buffering it.
+    pub async fn put_object_from_file<K: Into<String>>(
+        &self,
+        key: K,
+        file: File,
+        options: PutObjectOptions,
+    ) -> Result<PutObjectOutput> {
+        let body = ByteStream::from_file(file).await?;
+        self.put_object(key, body, options).await
+    }
+
+    /// Streams `reader` to `key` without buffering it. Its `size` should be
+    /// given when known, the upload is sent chunked otherwise.
+    pub async fn put_object_from_reader<K, R>(
+        &self,
+        key: K,
+        reader: R,
+        size: Option<u64>,
+        options: PutObjectOptions,
+    ) -> Result<PutObjectOutput>
+    where
+        K: Into<String>,
+        R: AsyncRead + Send + 'static,
+    {
+        let body = ByteStream::from_async_read(reader, size.map(|size| size as usize));
+        self.put_object(key, body, options).await
+    }
+
+    /// Downloads the object, or the requested range of it.
+    pub async fn get_object(&self, request: GetObjectRequest) -> Result<GetObjectOutput> {
+        let mut rqst = self.get_request(request.key.as_str());
+        let mut headers = Vec::new();
+        if let Some((first, last)) = request.range {
+            let last = last.map(|last| last.to_string()).unwrap_or_default();
+            headers.push((header::RANGE, format!("bytes={}-{}", first, last)));
+        }
+        if request.range_behavior_standard {
+            headers.push((
+                HeaderName::from_static("x-oss-range-behavior"),
+                "standard".to_owned(),
+            ));
+        }
+        if let Some(ref if_match) = request.if_match {
+            headers.push((header::IF_MATCH, if_match.clone()));
+        }
+        if let Some(ref if_none_match) = request.if_none_match {
+            headers.push((header::IF_NONE_MATCH, if_none_match.clone()));
+        }
+        if let Some(since) = request.if_modified_since {
+            headers.push((
+                header::IF_MODIFIED_SINCE,
+                since.format(HTTP_DATE).to_string(),
+            ));
+        }
+        if let Some(since) = request.if_unmodified_since {
+            headers.push((
+                header::IF_UNMODIFIED_SINCE,
+                since.format(HTTP_DATE).to_string(),
+            ));
+        }
+        if let Some(traffic_limit) = request.traffic_limit {
+            headers.push((
+                HeaderName::from_static("x-oss-traffic-limit"),
+                traffic_limit.to_string(),
+            ));
+        }
+        if let Some(ref accept_encoding) = request.accept_encoding {
+            headers.push((header::ACCEPT_ENCODING, accept_encoding.clone()));
+        }
+        for (k, v) in headers {
+            rqst.add_header(k, HeaderValue::from_str(&v)?);
+        }
+        let params = [
+            ("response-content-type", &request.response_content_type),
+            (
+                "response-content-language",
+                &request.response_content_language,
+            ),
+            ("response-expires", &request.response_expires),
+            ("response-cache-control", &request.response_cache_control),
+            (
+                "response-content-disposition",
+                &request.response_content_disposition,
+            ),
+            (
+                "response-content-encoding",
+                &request.response_content_encoding,
+            ),
+            ("x-oss-process", &request.process),
+            ("versionId", &request.version_id),
+        ];
+        for (k, v) in params.iter() {
+            if let Some(v) = v {
+                rqst.add_params(*k, v.as_str());
+            }
+        }
+
+        let resp = self.send(rqst).await?;
+        Ok(GetObjectOutput {
+            content_length: resp
+                .header("content-length")
+                .and_then(|len| len.parse().ok())
+                .or_else(|| resp.body.size_hint().map(|len| len as u64))
+                .unwrap_or_default(),
+            content_range: resp.header("content-range").and_then(content_range),
+            content_type: resp.header("content-type").map(str::to_owned),
+            etag: resp.header("etag").unwrap_or_default().to_owned(),
+            last_modified: last_modified(&resp),
+            metadata: user_metadata(&resp),
+            storage_class: resp
+                .header("x-oss-storage-class")
+                .and_then(|class| class.parse().ok()),
+            version_id: resp.header("x-oss-version-id").map(str::to_owned),
+            crc64: crc64(&resp),
+            request_id: resp.request_id().to_owned(),
+            body: resp.body,
+        })
+    }
+
+    /// Downloads the object, or the requested range of it, into `writer`
+    /// chunk by chunk. Returns the number of bytes written.
+    pub async fn get_object_to_writer<W>(
+        &self,
+        request: GetObjectRequest,
+        writer: &mut W,
+    ) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut body = self.get_object(request).await?.body;
+        let mut written = 0;
+        while let Some(chunk) = body.try_next().await.map_err(errors::read)? {
+            writer.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+        }
+        writer.flush().await?;
+        Ok(written)
+    }
+
+    /// Downloads the object, or the requested range of it, to the file at
+    /// `path`, which is created or truncated. Returns the number of bytes written.
+    pub async fn get_object_to_file<P: AsRef<Path>>(
+        &self,
+        request: GetObjectRequest,
+        path: P,
+    ) -> Result<u64> {
+        let mut file = File::create(path).await?;
+        self.get_object_to_writer(request, &mut file).await
+    }
+
+    /// All the metadata of the object, without downloading it.
+    pub async fn head_object<K: Into<String>>(&self, key: K) -> Result<ObjectMeta> {
+        let resp = self.send(self.head_request(key)).await?;
+        Ok(object_meta(&resp))
+    }
+
+    /// A lighter `head_object`: only the size, ETag, last-modified, CRC64 and
+    /// version id are returned.
+    pub async fn get_object_meta<K: Into<String>>(&self, key: K) -> Result<ObjectMeta> {
+        let mut rqst = self.head_request(key);
+        rqst.add_params("objectMeta", None);
+        let resp = self.send(rqst).await?;
+        Ok(object_meta(&resp))
+    }
+
+    /// Whether the object exists, other errors than 404 Not Found are returned.
+    pub async fn object_exists<K: Into<String>>(&self, key: K) -> Result<bool> {
+        match self.get_object_meta(key).await {
+            Ok(_) => Ok(true),
+            Err(e) if e.is_not_found() => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+pub(crate) fn add_put_headers(rqst: &mut SignedRequest, options: &PutObjectOptions) -> Result<()> {
+    let mut headers = Vec::new();
+    if let Some(ref content_type) = options.content_type {
+        headers.push((header::CONTENT_TYPE, content_type.clone()));
+    }
+    if let Some(ref cache_control) = options.cache_control {
+        headers.push((header::CACHE_CONTROL, cache_control.clone()));
+    }
+    if let Some(ref content_disposition) = options.content_disposition {
+        headers.push((header::CONTENT_DISPOSITION, content_disposition.clone()));
+    }
+    if let Some(ref content_encoding) = options.content_encoding {
+        headers.push((header::CONTENT_ENCODING, content_encoding.clone()));
+    }
+    if let Some(expires) = options.expires {
+        headers.push((header::EXPIRES, expires.format(HTTP_DATE).to_string()));
+    }
+    if let Some(acl) = options.acl {
+        headers.push((HeaderName::from_static("x-oss-object-acl"), acl.to_string()));
+    }
+    if let Some(storage_class) = options.storage_class {
+        headers.push((
+            HeaderName::from_static("x-oss-storage-class"),
+            storage_class.to_string(),
+        ));
+    }
+    if !options.tagging.is_empty() {
+        let tagging = options
+            .tagging
+            .iter()
+            .map(|(k, v)| format!("{}={}", encode_query(k), encode_query(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+        headers.push((HeaderName::from_static("x-oss-tagging"), tagging));
+    }
+    if let Some(ref sse) = options.server_side_encryption {
+        headers.push((
+            HeaderName::from_static("x-oss-server-side-encryption"),
+            sse.algorithm().to_owned(),
+        ));
+        if let Some(key_id) = sse.key_id() {
+            headers.push((
+                HeaderName::from_static("x-oss-server-side-encryption-key-id"),
+                key_id.to_owned(),
+            ));
+        }
+    }
+    if options.forbid_overwrite {
+        headers.push((
+            HeaderName::from_static("x-oss-forbid-overwrite"),
+            "true".to_owned(),
+        ));
+    }
+    for (k, v) in headers {
+        rqst.add_header(k, HeaderValue::from_str(&v)?);
+    }
+    rqst.add_meta(
+        options
+            .metadata
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str())),
+    )
+}
+
+/// `x-oss-hash-crc64ecma`, the CRC-64/ECMA-182 OSS computed over the object.
+pub(crate) fn crc64(resp: &HttpResponse) -> Option<u64> {
+    resp.header("x-oss-hash-crc64ecma")
+        .and_then(|crc64| crc64.parse().ok())
+}
+
+fn object_meta(resp: &HttpResponse) -> ObjectMeta {
+    ObjectMeta {
+        size: resp
+            .header("content-length")
+            .and_then(|len| len.parse().ok())
+            .unwrap_or_default(),
+        etag: resp.header("etag").unwrap_or_default().to_owned(),
+        last_modified: last_modified(resp),
+        content_type: resp.header("content-type").map(str::to_owned),
+        storage_class: resp
+            .header("x-oss-storage-class")
+            .and_then(|class| class.parse().ok()),
+        object_type: resp
+            .header("x-oss-object-type")
+            .and_then(|object_type| object_type.parse().ok()),
+        crc64: crc64(resp),
+        version_id: resp.header("x-oss-version-id").map(str::to_owned),
+        restore: resp.header("x-oss-restore").and_then(RestoreStatus::parse),
+        server_side_encryption: resp
+            .header("x-oss-server-side-encryption")
+            .and_then(|algorithm| {
+                let key_id = resp
+                    .header("x-oss-server-side-encryption-key-id")
+                    .map(str::to_owned);
+                ServerSideEncryption::new(algorithm, key_id).ok()
+            }),
+        metadata: user_metadata(resp),
+        request_id: resp.request_id().to_owned(),
+    }
+}
+
+pub(crate) fn last_modified(resp: &HttpResponse) -> Option<DateTime<Utc>> {
+    resp.header("last-modified")
+        .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// `x-oss-meta-*` headers, without the prefix.
+pub(crate) fn user_metadata(resp: &HttpResponse) -> BTreeMap<String, String> {
+    resp.headers
+        .iter()
+        .filter_map(|(k, v)| {
+            let key = k.as_str().strip_prefix(OSS_PREFIX)?;
+            Some((key.to_owned(), v.to_str().ok()?.to_owned()))
+        })
+        .collect()
+}
+
+/// `bytes 0-9/44`
+fn content_range(s: &str) -> Option<ContentRange> {
+    let (range, size) = s.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, end) = range.split_once('-')?;
+    Some(ContentRange {
+        start: start.parse().ok()?,
+        end: end.parse().ok()?,
+        size: size.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_util::{mock_client, temp_path, MockResponse, MockServer},
+        Acl, ObjectType, StorageClass,
+    };
+    use chrono::TimeZone;
+    use http::StatusCode;
+
+    #[tokio::test]
+    async fn put_object_test() {
+        let server = MockServer::start(|rqst| {
+            if rqst.header("x-oss-forbid-overwrite") == Some("true") {
+                return MockResponse::status(409).body("FileAlreadyExists");
+            }
+            MockResponse::ok("")
+                .header("etag", "\"5B3C1A2E053D763E1B002CC607C5A0FE\"")
+                .header("x-oss-hash-crc64ecma", "18446744073709551615")
+                .header("x-oss-version-id", "CAEQ")
+                .header("x-oss-request-id", "534B371674E88A4D8906")
+        })
+        .await;
+        let oss_instance = mock_client(&server);
+        let mut options = PutObjectOptions {
+            content_type: Some("text/plain".to_owned()),
+            cache_control: Some("no-cache".to_owned()),
+            content_disposition: Some("attachment; filename=a.txt".to_owned()),
+            content_encoding: Some("identity".to_owned()),
+            expires: Some(Utc.timestamp_opt(1700000000, 0).unwrap()),
+            acl: Some(Acl::Private),
+            storage_class: Some(StorageClass::InfrequentAccess),
+            server_side_encryption: Some(ServerSideEncryption::Kms {
+                key_id: Some("key".to_owned()),
+            }),
+            ..Default::default()
+        };
+        options
+            .metadata
+            .insert("Author".to_owned(), "x-oss-meta-like value".to_owned());
+        options.tagging.insert("a b".to_owned(), "1&2".to_owned());
+        options.tagging.insert("c".to_owned(), "".to_owned());
+
+        let output = oss_instance
+            .put_object("dir/a.txt", "hello", options.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            PutObjectOutput {
+                etag: "\"5B3C1A2E053D763E1B002CC607C5A0FE\"".to_owned(),
+                crc64: Some(u64::MAX),
+                version_id: Some("CAEQ".to_owned()),
+                request_id: "534B371674E88A4D8906".to_owned(),
+            }
+        );
+
+        let rqst = &server.requests()[0];
+        assert_eq!(rqst.method, "PUT");
+        assert_eq!(rqst.path, "/bucket/dir/a.txt");
+        assert_eq!(rqst.body, b"hello");
+        assert_eq!(rqst.header("content-type"), Some("text/plain"));
+        assert_eq!(rqst.header("cache-control"), Some("no-cache"));
+        assert_eq!(
+            rqst.header("content-disposition"),
+            Some("attachment; filename=a.txt")
+        );
+        assert_eq!(rqst.header("content-encoding"), Some("identity"));
+        assert_eq!(
+            rqst.header("expires"),
+            Some("Tue, 14 Nov 2023 22:13:20 GMT")
+        );
+        assert_eq!(
+            rqst.header("x-oss-meta-author"),
+            Some("x-oss-meta-like value")
+        );
+        assert_eq!(rqst.header("x-oss-object-acl"), Some("private"));
+        assert_eq!(rqst.header("x-oss-storage-class"), Some("IA"));
+        assert_eq!(rqst.header("x-oss-tagging"), Some("a%20b=1%262&c="));
+        assert_eq!(rqst.header("x-oss-server-side-encryption"), Some("KMS"));
+        assert_eq!(
+            rqst.header("x-oss-server-side-encryption-key-id"),
+            Some("key")
+        );
+        assert_eq!(rqst.header("x-oss-forbid-overwrite"), None);
+
+        options.forbid_overwrite = true;
+        let err = oss_instance
+            .put_object("dir/a.txt", "hello", options)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("409"));
+    }
+
+    #[tokio::test]
+    async fn get_object_test() {
+        let server = MockServer::start(|rqst| {
+            if rqst.header("if-none-match") == Some("\"etag\"") {
+                return MockResponse::status(304);
+            }
+            MockResponse::status(206)
+                .body("ello")
+                .header("content-range", "bytes 1-4/5")
+                .header("content-type", "text/plain")
+                .header("etag", "\"etag\"")
+                .header("last-modified", "Tue, 14 Nov 2023 22:13:20 GMT")
+                .header("x-oss-meta-author", "me")
+                .header("x-oss-storage-class", "Archive")
+                .header("x-oss-hash-crc64ecma", "42")
+                .header("x-oss-request-id", "id")
+        })
+        .await;
+        let oss_instance = mock_client(&server);
+        let mut request = GetObjectRequest::new("dir/a.txt");
+        request.range = Some((1, None));
+        request.if_modified_since = Some(Utc.timestamp_opt(1600000000, 0).unwrap());
+        request.response_content_disposition = Some("attachment; filename=\"a b.txt\"".to_owned());
+        request.process = Some("image/resize,w_100".to_owned());
+        request.version_id = Some("CAEQ".to_owned());
+        request.traffic_limit = Some(819200);
+        let output = oss_instance.get_object(request.clone()).await.unwrap();
+        assert_eq!(output.content_length, 4);
+        assert_eq!(
+            output.content_range,
+            Some(ContentRange {
+                start: 1,
+                end: 4,
+                size: 5
+            })
+        );
+        assert_eq!(output.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(output.etag, "\"etag\"");
+        assert_eq!(
+            output.last_modified,
+            Some(Utc.timestamp_opt(1700000000, 0).unwrap())
+        );
+        assert_eq!(output.metadata["author"], "me");
+        assert_eq!(output.storage_class, Some(StorageClass::Archive));
+        assert_eq!(output.crc64, Some(42));
+        assert_eq!(output.request_id, "id");
+        let body: Vec<u8> = output
+            .body
+            .map_ok(|bytes| bytes.to_vec())
+            .try_concat()
+            .await
+            .unwrap();
+        assert_eq!(body, b"ello");
+
+        let rqst = &server.requests()[0];
+        assert_eq!(rqst.method, "GET");
+        assert_eq!(rqst.header("range"), Some("bytes=1-"));
+        assert_eq!(
+            rqst.header("if-modified-since"),
+            Some("Sun, 13 Sep 2020 12:26:40 GMT")
+        );
+        assert_eq!(rqst.header("x-oss-traffic-limit"), Some("819200"));
+        assert_eq!(
+            rqst.param("response-content-disposition").as_deref(),
+            Some("attachment; filename=\"a b.txt\"")
+        );
+        assert_eq!(
+            rqst.param("x-oss-process").as_deref(),
+            Some("image/resize,w_100")
+        );
+        assert_eq!(rqst.param("versionId").as_deref(), Some("CAEQ"));
+
+        request.if_none_match = Some("\"etag\"".to_owned());
+        let err = oss_instance.get_object(request).await.unwrap_err();
+        assert!(err.to_string().contains("304"));
+    }
+
+    #[tokio::test]
+    async fn head_object_test() {
+        let server = MockServer::start(|rqst| match rqst.path.as_str() {
+            "/bucket/missing" => MockResponse::status(404),
+            "/bucket/forbidden" => MockResponse::status(403),
+            "/bucket/huge" => MockResponse::status(403).header("content-length", "5497558138880"),
+            _ => MockResponse::ok("")
+                .header("content-length", "344606")
+                .header("content-type", "image/jpg")
+                .header("etag", "\"fba9dede5f27731c9771645a39863328\"")
+                .header("last-modified", "Tue, 14 Nov 2023 22:13:20 GMT")
+                .header("x-oss-object-type", "Multipart")
+                .header("x-oss-storage-class", "Archive")
+                .header(
+                    "x-oss-restore",
+                    "ongoing-request=\"false\", expiry-date=\"Sun, 16 Apr 2017 08:12:33 GMT\"",
+                )
+                .header("x-oss-server-side-encryption", "KMS")
+                .header("x-oss-server-side-encryption-key-id", "key")
+                .header("x-oss-hash-crc64ecma", "42")
+                .header("x-oss-version-id", "CAEQ")
+                .header("x-oss-meta-author", "me")
+                .header("x-oss-request-id", "id"),
+        })
+        .await;
+        let oss_instance = mock_client(&server);
+        let meta = oss_instance.head_object("a.jpg").await.unwrap();
+        assert_eq!(
+            meta,
+            ObjectMeta {
+                size: 344606,
+                etag: "\"fba9dede5f27731c9771645a39863328\"".to_owned(),
+                last_modified: Some(Utc.timestamp_opt(1700000000, 0).unwrap()),
+                content_type: Some("image/jpg".to_owned()),
+                storage_class: Some(StorageClass::Archive),
+                object_type: Some(ObjectType::Multipart),
+                crc64: Some(42),
+                version_id: Some("CAEQ".to_owned()),
+                restore: Some(RestoreStatus::Restored {
+                    expiry: Some(Utc.timestamp_opt(1492330353, 0).unwrap())
+                }),
+                server_side_encryption: Some(ServerSideEncryption::Kms {
+                    key_id: Some("key".to_owned())
+                }),
+                metadata: vec![("author".to_owned(), "me".to_owned())]
+                    .into_iter()
+                    .collect(),
+                request_id: "id".to_owned(),
+            }
+        );
+        assert_eq!(
+            RestoreStatus::parse("ongoing-request=\"true\""),
+            Some(RestoreStatus::InProgress)
+        );
+
+        assert_eq!(
+            oss_instance.get_object_meta("a.jpg").await.unwrap().size,
+            344606
+        );
+        assert!(oss_instance.object_exists("a.jpg").await.unwrap());
+        assert!(!oss_instance.object_exists("missing").await.unwrap());
+        let err = oss_instance.object_exists("forbidden").await.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::FORBIDDEN));
+        // Not a body of 5 TiB to reserve room for
+        let resp = oss_instance
+            .sign_and_dispatch(oss_instance.head_request("huge"))
+            .await
+            .unwrap();
+        assert_eq!(resp.body.size_hint().unwrap_or_default(), 0);
+        assert!(resp.collect().await.unwrap().is_empty());
+        let err = oss_instance.head_object("huge").await.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::FORBIDDEN));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "HEAD");
+        assert_eq!(requests[0].query, "");
+        assert_eq!(requests[1].method, "HEAD");
+        assert_eq!(requests[1].query, "objectMeta");
+    }
+
+    #[tokio::test]
+    async fn put_object_streaming_test() {
+        let server = MockServer::start(|_| MockResponse::ok("")).await;
+        let oss_instance = mock_client(&server);
+        let data: Vec<u8> = (0..200 * 1024).map(|i| i as u8).collect();
+
+        let reader = std::io::Cursor::new(data.clone());
+        oss_instance
+            .put_object_from_reader("reader", reader, None, PutObjectOptions::default())
+            .await
+            .unwrap();
+        let path = temp_path("put_object_from_file");
+        std::fs::write(&path, &data).unwrap();
+        let file = File::open(&path).await.unwrap();
+        oss_instance
+            .put_object_from_file("file", file, PutObjectOptions::default())
+            .await
+            .unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("transfer-encoding"), Some("chunked"));
+        assert_eq!(requests[0].header("content-length"), None);
+        assert_eq!(requests[0].body, data);
+        assert_eq!(requests[1].header("transfer-encoding"), None);
+        assert_eq!(requests[1].header("content-length"), Some("204800"));
+        assert_eq!(requests[1].body, data);
+    }
+
+    #[tokio::test]
+    async fn get_object_to_file_test() {
+        let data: Vec<u8> = (0..200 * 1024).map(|i| i as u8).collect();
+        let body = data.clone();
+        let server = MockServer::start(move |_| MockResponse::ok(body.clone())).await;
+        let oss_instance = mock_client(&server);
+
+        let mut buf = Vec::new();
+        let written = oss_instance
+            .get_object_to_writer(GetObjectRequest::new("a"), &mut buf)
+            .await
+            .unwrap();
+        assert_eq!(written, data.len() as u64);
+        assert_eq!(buf, data);
+
+        let path = temp_path("get_object_to_file");
+        let written = oss_instance
+            .get_object_to_file(GetObjectRequest::new("a"), &path)
+            .await
+            .unwrap();
+        assert_eq!(written, data.len() as u64);
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn truncated_body_test() {
+        // Announces more than it sends, the connection is closed mid-body.
+        let server =
+            MockServer::start(|_| MockResponse::ok("short").header("content-length", "100")).await;
+        let oss_instance = mock_client(&server);
+        let resp = oss_instance
+            .sign_and_dispatch(oss_instance.get_request("a"))
+            .await
+            .unwrap();
+        let err = resp.collect().await.unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("request or response body error"));
+
+        let mut buf = Vec::new();
+        let err = oss_instance
+            .get_object_to_writer(GetObjectRequest::new("a"), &mut buf)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("request or response body error"));
+    }
+}
@@ -1,23 +1,38 @@
-use base64::encode;
-use crypto::{hmac::Hmac, mac::Mac, sha1::Sha1};
-use http::{header::HeaderName, HeaderValue, Method};
+use chrono::Utc;
+use http::{header::HeaderName, HeaderMap, HeaderValue, Method};
 
-use std::{borrow::Cow, collections::BTreeMap, str::FromStr};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crate::{
-    http_client::{HttpResponse, SignAndDispatch, SignedRequest},
-    statics::{reqwest_client, CONTENT_MD5, CONTENT_TYPE, OSS_CANONICALIZED_PREFIX},
-    Error, OssClient, Region, Result, Schema, OSS_PREFIX,
+    http_client::{HttpError, HttpResponse, SignAndDispatch, SignedRequest, SignedRequestPayload},
+    statics::reqwest_client,
+    xml, AddressingStyle, ByteStream, Credentials, CredentialsProvider, EndpointType, Error,
+    OssClient, Region, Result, RetryPolicy, Schema, ServiceError, SignatureVersion, StaticProvider,
+    Timeouts, OSS_PREFIX,
 };
 
-#[derive(Debug)]
+/// Longest validity OSS accepts for a V4 signed url, 7 days.
+const MAX_V4_EXPIRES: Duration = Duration::from_secs(7 * 24 * 3600);
+
 pub struct OSSClient<C: SignAndDispatch + Send + Sync> {
     pub client: C,
     pub region: Region,
-    access_key_id: String,
-    access_key_secret: String,
+    credentials: Arc<dyn CredentialsProvider>,
     bucket: String,
     schema: Schema,
+    endpoint_type: EndpointType,
+    addressing_style: AddressingStyle,
+    signature_version: SignatureVersion,
+    retry_policy: RetryPolicy,
+    timeouts: Timeouts,
+    default_headers: HeaderMap,
 }
 impl OssClient {
     pub fn new_with_reqwest<'a, R, S, B, S1, S2>(
@@ -34,21 +49,20 @@ impl OssClient {
         S1: Into<String>,
         S2: Into<String>,
     {
-        Self {
-            client: reqwest_client(),
-            region: region.as_ref().parse().unwrap_or_default(),
-            schema: schema
-                .into()
-                .and_then(|_schema| _schema.parse().ok())
-                .unwrap_or_default(),
-            bucket: bucket.into().unwrap_or_default().to_string(),
-            access_key_id: access_key_id.into(),
-            access_key_secret: access_key_secret.into(),
-        }
+        Self::new(
+            reqwest_client(),
+            region,
+            schema,
+            bucket,
+            access_key_id,
+            access_key_secret,
+        )
     }
 }
 
 impl<C: SignAndDispatch + Send + Sync> OSSClient<C> {
+    /// Unknown regions and schemas fall back to the defaults, while
+    /// `OSSClient::builder` rejects them.
     pub fn new<'a, R, S, B, S1, S2>(
         client: C,
         region: R,
@@ -64,20 +78,124 @@ impl<C: SignAndDispatch + Send + Sync> OSSClient<C> {
         S1: Into<String>,
         S2: Into<String>,
     {
-        OSSClient {
+        Self::from_parts(
             client,
-            region: region.as_ref().parse().unwrap_or_default(),
-            schema: schema
+            region.as_ref().parse().unwrap_or_default(),
+            schema
                 .into()
                 .and_then(|_schema| _schema.parse().ok())
                 .unwrap_or_default(),
-            bucket: bucket.into().unwrap_or_default().to_string(),
-            access_key_id: access_key_id.into(),
-            access_key_secret: access_key_secret.into(),
+            bucket.into().unwrap_or_default(),
+            Arc::new(StaticProvider::new(access_key_id, access_key_secret)),
+        )
+    }
+    pub(crate) fn from_parts<B: Into<String>>(
+        client: C,
+        region: Region,
+        schema: Schema,
+        bucket: B,
+        credentials: Arc<dyn CredentialsProvider>,
+    ) -> Self {
+        OSSClient {
+            client,
+            region,
+            schema,
+            bucket: bucket.into(),
+            credentials,
+            endpoint_type: EndpointType::default(),
+            addressing_style: AddressingStyle::default(),
+            signature_version: SignatureVersion::default(),
+            retry_policy: RetryPolicy::default(),
+            timeouts: Timeouts::default(),
+            default_headers: HeaderMap::new(),
         }
     }
-    pub fn get_access_key(&self) -> (&str, &str) {
-        (&self.access_key_id, &self.access_key_secret)
+    /// Like `new`, but signs with whatever `provider` hands out at request
+    /// time, e.g. `ChainProvider::default()`.
+    pub fn with_credentials_provider<'a, R, S, B, P>(
+        client: C,
+        region: R,
+        schema: S,
+        bucket: B,
+        provider: P,
+    ) -> Self
+    where
+        R: AsRef<str>,
+        S: Into<Option<&'a str>>,
+        B: Into<Option<&'a str>>,
+        P: CredentialsProvider + 'static,
+    {
+        let mut oss_client = Self::new(client, region, schema, bucket, "", "");
+        oss_client.set_credentials_provider(provider);
+        oss_client
+    }
+    /// Replaces the access key pair given at construction, e.g. by temporary
+    /// STS credentials carrying a security token.
+    pub fn set_credentials(&mut self, credentials: Credentials) {
+        self.credentials = Arc::new(StaticProvider::from(credentials));
+    }
+    /// Fetches credentials from `provider` whenever a request is signed, so
+    /// rotated or refreshed keys are picked up without rebuilding the client.
+    pub fn set_credentials_provider<P>(&mut self, provider: P)
+    where
+        P: CredentialsProvider + 'static,
+    {
+        self.credentials = Arc::new(provider);
+    }
+    /// The credentials the next request would be signed with.
+    pub async fn credentials(&self) -> Result<Credentials> {
+        Ok(self.credentials.credentials().await?)
+    }
+    /// Chooses between the public, internal (VPC) and accelerate endpoints
+    /// for all requests generated by this client.
+    pub fn set_endpoint_type(&mut self, endpoint_type: EndpointType) {
+        self.endpoint_type = endpoint_type;
+    }
+    pub fn get_endpoint_type(&self) -> EndpointType {
+        self.endpoint_type
+    }
+    /// Virtual-hosted (the default), path-style for emulators and proxies, or
+    /// CNAME when the region's endpoint is a domain bound to the bucket.
+    pub fn set_addressing_style(&mut self, addressing_style: AddressingStyle) {
+        self.addressing_style = addressing_style;
+    }
+    pub fn get_addressing_style(&self) -> AddressingStyle {
+        self.addressing_style
+    }
+    /// Signs every request of this client with V1 (the default) or V4.
+    pub fn set_signature_version(&mut self, signature_version: SignatureVersion) {
+        self.signature_version = signature_version;
+    }
+    pub fn get_signature_version(&self) -> SignatureVersion {
+        self.signature_version
+    }
+    /// Retries network errors and transient failures a few times by default.
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+    pub fn get_retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+    /// Request and read timeouts apply from the next request on, and can be
+    /// overridden per request with `SignedRequest::set_timeout` and
+    /// `set_read_timeout`. The connect timeout is that of the HTTP client:
+    /// `new_with_reqwest` shares one built with the default, and
+    /// `OSSClient::builder` builds one with the configured timeout.
+    pub fn set_timeouts(&mut self, timeouts: Timeouts) {
+        self.timeouts = timeouts;
+    }
+    pub fn get_timeouts(&self) -> &Timeouts {
+        &self.timeouts
+    }
+    /// Added to every request made from now on, unless set on the request.
+    pub fn set_default_headers(&mut self, headers: HeaderMap) {
+        self.default_headers = headers;
+    }
+    pub fn get_default_headers(&self) -> &HeaderMap {
+        &self.default_headers
+    }
+    pub fn get_bucket(&self) -> &str {
+        &self.bucket
     }
     pub fn get_request<'a, S>(&self, object: S) -> SignedRequest
     where
@@ -85,12 +203,12 @@ impl<C: SignAndDispatch + Send + Sync> OSSClient<C> {
     {
         self.generate_request(Method::GET, object.into().unwrap_or_default(), None)
     }
-    pub fn put_request<'a, S, P>(&self, object: S, payload: P) -> SignedRequest
+    pub fn put_request<S, P>(&self, object: S, payload: P) -> SignedRequest
     where
         S: Into<String>,
-        P: Into<Option<Box<[u8]>>>,
+        P: Into<SignedRequestPayload>,
     {
-        self.generate_request(Method::PUT, object, payload)
+        self.generate_request(Method::PUT, object, Some(payload.into()))
     }
     pub fn head_request<S>(&self, object: S) -> SignedRequest
     where
@@ -104,87 +222,212 @@ impl<C: SignAndDispatch + Send + Sync> OSSClient<C> {
     {
         self.generate_request(Method::DELETE, object, None)
     }
+    pub fn post_request<S, P>(&self, object: S, payload: P) -> SignedRequest
+    where
+        S: Into<String>,
+        P: Into<SignedRequestPayload>,
+    {
+        self.generate_request(Method::POST, object, Some(payload.into()))
+    }
+    /// Signs and sends `request`, again as long as the retry policy allows
+    /// it. The last response is returned whatever its status. After a network
+    /// failure, only an idempotent request is sent again, see
+    /// `SignedRequest::set_idempotent`.
     pub async fn sign_and_dispatch(&self, request: SignedRequest) -> Result<HttpResponse> {
-        self.client
-            .sign_and_dispatch(request, None)
-            .await
-            .map_err(Error::from)
+        let policy = &self.retry_policy;
+        let started = Instant::now();
+        let mut request = request;
+        let mut attempt = 1;
+        loop {
+            let left = policy
+                .deadline
+                .map(|deadline| deadline.saturating_sub(started.elapsed()));
+            let timeout = match (request.timeout().or(self.timeouts.request), left) {
+                (Some(timeout), Some(left)) => Some(timeout.min(left)),
+                (timeout, left) => timeout.or(left),
+            };
+            let timeouts = Timeouts {
+                request: timeout,
+                read: request.read_timeout().or(self.timeouts.read),
+                ..self.timeouts
+            };
+            let idempotent = request.is_idempotent();
+            // Streams cannot be replayed, nor can the last attempt be retried
+            let next = if attempt < policy.max_attempts {
+                request.try_clone()
+            } else {
+                None
+            };
+            let result = self
+                .client
+                .sign_and_dispatch(request, &*self.credentials, &timeouts)
+                .await
+                .map_err(Error::from);
+            let (result, retry) = match result {
+                Ok(resp) => self.should_retry(resp).await,
+                // The request may have been applied all the same
+                Err(e) => {
+                    let retry = e.is_retryable() && idempotent;
+                    (Err(e), retry)
+                }
+            };
+            let delay = policy.delay(attempt);
+            let in_time = policy
+                .deadline
+                .is_none_or(|deadline| started.elapsed() + delay < deadline);
+            match next {
+                Some(next) if retry && in_time => {
+                    log::debug!("retrying {} request in {:?}", next.method(), delay);
+                    tokio::time::sleep(delay).await;
+                    request = next;
+                    attempt += 1;
+                }
+                _ => return result,
+            }
+        }
+    }
+    /// Whether the retry policy asks to send the request of `resp` again.
+    /// The body of a non-2xx response is read to look for a retryable error
+    /// code, and put back.
+    async fn should_retry(&self, mut resp: HttpResponse) -> (Result<HttpResponse>, bool) {
+        let policy = &self.retry_policy;
+        if resp.status.is_success() {
+            return (Ok(resp), false);
+        }
+        if policy.retryable_statuses.contains(&resp.status) {
+            return (Ok(resp), true);
+        }
+        if policy.retryable_codes.is_empty() {
+            return (Ok(resp), false);
+        }
+        let error = HttpResponse {
+            status: resp.status,
+            headers: HeaderMap::new(),
+            body: std::mem::replace(&mut resp.body, ByteStream::from(Vec::new())),
+        };
+        let body = match error.collect().await {
+            Ok(body) => body,
+            Err(e) => return (Err(e.into()), true),
+        };
+        let retry = xml::from_slice::<ServiceError>(&body)
+            .is_ok_and(|e| policy.retryable_codes.contains(&e.code));
+        resp.body = ByteStream::from(body.to_vec());
+        (Ok(resp), retry)
+    }
+    /// Like `sign_and_dispatch`, but non-2xx responses are turned into a
+    /// `ServiceError`.
+    pub async fn send(&self, request: SignedRequest) -> Result<HttpResponse> {
+        let resp = self.sign_and_dispatch(request).await?;
+        if !resp.status.is_success() {
+            return Err(ServiceError::from_response(resp).await?.into());
+        }
+        Ok(resp)
     }
-    pub fn get_signed_url<'a, H>(
+    /// `params` is the raw (not yet encoded) query string, e.g.
+    /// `response-content-type=text/plain&x-oss-process=image/resize,w_100`.
+    pub async fn get_signed_url<'a, H>(
         &self,
         object: &str,
         verb: &str,
         expires: u64,
         params: &str,
         headers: H,
-    ) -> String
+    ) -> Result<String>
     where
         H: Into<Option<BTreeMap<&'a str, &'a str>>>,
     {
-        let mut content_type = "";
-        let mut content_md5 = "";
-        let mut oss_headers_str = String::new();
+        let mut rqst = self.generate_request(Method::GET, object, None);
+        rqst.set_method(Method::from_str(verb).map_err(HttpError::from)?);
         if let Some(_headers) = headers.into() {
             for (k, v) in _headers {
-                if k.starts_with(OSS_CANONICALIZED_PREFIX) {
-                    oss_headers_str += k;
-                    oss_headers_str += ":";
-                    oss_headers_str += v;
-                    oss_headers_str += "\n";
-                } else if k == CONTENT_TYPE {
-                    content_type = v;
-                } else if k == CONTENT_MD5 {
-                    content_md5 = v;
-                }
+                rqst.add_header(HeaderName::from_str(k)?, HeaderValue::from_str(v)?);
             }
         }
-        let oss_resource_str = get_oss_subresource_signed_str(&self.bucket, object, params);
-        let sign_str = format!(
-            "{}\n{}\n{}\n{}\n{}{}",
-            verb, content_md5, content_type, expires, oss_headers_str, oss_resource_str
-        );
-        let mut hasher = Hmac::new(Sha1::new(), self.access_key_secret.as_bytes());
-        hasher.input(sign_str.as_bytes());
-        let sign_str_base64 = encode(hasher.result().code());
+        for param in params.split('&').filter(|param| !param.is_empty()) {
+            match param.split_once('=') {
+                Some((k, v)) => rqst.add_params(k, v),
+                None => rqst.add_params(param, None),
+            }
+        }
+        let credentials = self.credentials().await?;
+        Ok(rqst.presign_v1(&credentials, expires)?.into())
+    }
 
-        let auth_params = format!(
-            "OSSAccessKeyId={}&Expires={}&Signature={}",
-            self.access_key_id, expires, sign_str_base64
-        );
-        self.host(object, &auth_params)
+    /// Presigns `object` with a V4 query signature, valid for `expires`, at most
+    /// 7 days or a config error is returned.
+    /// `headers` are signed and must be sent along by whoever uses the url,
+    /// `params` are extra query params such as the `response-*` overrides.
+    pub async fn get_signed_url_v4<'a, H, P>(
+        &self,
+        object: &str,
+        method: Method,
+        expires: Duration,
+        headers: H,
+        params: P,
+    ) -> Result<String>
+    where
+        H: Into<Option<HeaderMap>>,
+        P: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        if expires > MAX_V4_EXPIRES {
+            return Err(Error::config(format!(
+                "a V4 signed url expires within {} seconds, not {}",
+                MAX_V4_EXPIRES.as_secs(),
+                expires.as_secs()
+            )));
+        }
+        let mut rqst = self.generate_request(method, object, None);
+        if let Some(_headers) = headers.into() {
+            for (k, v) in _headers.iter() {
+                rqst.add_header(k.clone(), v.clone());
+            }
+        }
+        for (k, v) in params {
+            rqst.add_params(k, v);
+        }
+        let credentials = self.credentials().await?;
+        Ok(rqst.presign_v4(&credentials, Utc::now(), expires)?.into())
     }
 
-    fn generate_request<'a, S1, P>(&self, method: Method, object: S1, payload: P) -> SignedRequest
+    fn generate_request<S1>(
+        &self,
+        method: Method,
+        object: S1,
+        payload: Option<SignedRequestPayload>,
+    ) -> SignedRequest
     where
         S1: Into<String>,
-        P: Into<Option<Box<[u8]>>>,
     {
-        let mut signed_rqst = SignedRequest::new(
-            method,
-            &self.region,
-            &self.bucket,
-            object,
-            &self.access_key_id,
-            &self.access_key_secret,
-            self.schema,
+        let mut signed_rqst =
+            SignedRequest::new(method, &self.region, &self.bucket, object, self.schema);
+        signed_rqst.set_endpoint_type(self.endpoint_type);
+        signed_rqst.set_addressing_style(self.addressing_style);
+        signed_rqst.set_signature_version(self.signature_version);
+        signed_rqst.add_headers(
+            self.default_headers
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone())),
         );
-        let content_length = if let Some(_payload) = payload.into() {
-            signed_rqst.load(_payload.to_owned())
-        } else {
-            0
-        };
-        signed_rqst.set_content_length(content_length);
+        match payload {
+            Some(_payload) => {
+                signed_rqst.load(_payload);
+            }
+            None => signed_rqst.set_content_length(0),
+        }
         signed_rqst
     }
-    fn host(&self, object: &str, params: &str) -> String {
-        format!(
-            "{}://{}.{}/{}?{}",
-            self.schema,
-            self.bucket,
-            self.region.endpoint(),
-            object,
-            params,
-        )
+}
+
+impl<C: SignAndDispatch + Send + Sync> fmt::Debug for OSSClient<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OSSClient")
+            .field("region", &self.region)
+            .field("bucket", &self.bucket)
+            .field("schema", &self.schema)
+            .field("endpoint_type", &self.endpoint_type)
+            .field("addressing_style", &self.addressing_style)
+            .field("signature_version", &self.signature_version)
+            .finish()
     }
 }
 
@@ -195,26 +438,399 @@ impl SignedRequest {
     ) -> Result<()> {
         for (k, v) in meta {
             let key = HeaderName::from_str(Self::add_oss_meta_prefix(k).as_ref())?;
-            let value = HeaderValue::from_str(Self::add_oss_meta_prefix(v).as_ref())?;
+            let value = HeaderValue::from_str(v)?;
             self.add_header(key, value);
         } // self.add_headers(meta)
         Ok(())
     }
-    fn add_oss_meta_prefix(s: &str) -> Cow<str> {
+    fn add_oss_meta_prefix(s: &str) -> Cow<'_, str> {
         if !s.starts_with(OSS_PREFIX) {
             Cow::from(format!("{}{}", OSS_PREFIX, s))
         } else {
-            Cow::Borrowed(&*s)
+            Cow::Borrowed(s)
         }
     }
 }
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ListObjectsV2Options;
     const FILE_NAME: &str = "rust_oss_sdk_test";
     const BUF: &[u8] = "This is just a put test".as_bytes();
 
     #[tokio::test]
+    async fn path_style_signed_url_test() {
+        let mut oss_instance = OSSClient::new_with_reqwest(
+            "cn-hangzhou",
+            "http",
+            "bucket",
+            "access_key_id",
+            "access_key_secret",
+        );
+        let virtual_hosted = oss_instance
+            .get_signed_url("object", "GET", 1700000000, "", None)
+            .await
+            .unwrap();
+        assert!(virtual_hosted.starts_with(
+            "http://bucket.oss-cn-hangzhou.aliyuncs.com/object?Expires=1700000000&OSSAccessKeyId=access_key_id&Signature="
+        ));
+
+        oss_instance.region = Region::custom("cn-hangzhou", "127.0.0.1:9000");
+        oss_instance.set_addressing_style(AddressingStyle::Path);
+        let path_style = oss_instance
+            .get_signed_url("object", "GET", 1700000000, "", None)
+            .await
+            .unwrap();
+        assert!(path_style.starts_with(
+            "http://127.0.0.1:9000/bucket/object?Expires=1700000000&OSSAccessKeyId=access_key_id&Signature="
+        ));
+        // The canonical resource is `/bucket/object` in both styles.
+        assert_eq!(
+            virtual_hosted.split_once("Signature=").unwrap().1,
+            path_style.split_once("Signature=").unwrap().1
+        );
+    }
+
+    #[tokio::test]
+    async fn encoded_signed_url_test() {
+        let oss_instance =
+            OSSClient::new_with_reqwest("cn-hangzhou", "https", "bucket", "id", "secret");
+        let signed_url = oss_instance
+            .get_signed_url(
+                "dir/a b#c+d",
+                "GET",
+                1700000000,
+                "response-content-type=text/plain",
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(signed_url
+            .starts_with("https://bucket.oss-cn-hangzhou.aliyuncs.com/dir/a%20b%23c%2Bd?"));
+        assert!(signed_url.ends_with("&response-content-type=text%2Fplain"));
+        let url = url::Url::parse(&signed_url).unwrap();
+        let (_, signature) = url.query_pairs().find(|(k, _)| k == "Signature").unwrap();
+        assert_eq!(base64::decode(signature.as_bytes()).unwrap().len(), 20);
+    }
+
+    #[tokio::test]
+    async fn signed_url_v4_test() {
+        let mut oss_instance =
+            OSSClient::new_with_reqwest("cn-hangzhou", "https", "bucket", "id", "secret");
+        oss_instance.set_addressing_style(AddressingStyle::Path);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            http::header::CONTENT_TYPE,
+            HeaderValue::from_static("text/plain"),
+        );
+        let signed_url = oss_instance
+            .get_signed_url_v4(
+                "dir/a b",
+                Method::PUT,
+                Duration::from_secs(3600),
+                headers,
+                vec![("response-content-disposition", "attachment")],
+            )
+            .await
+            .unwrap();
+        assert!(signed_url.starts_with("https://oss-cn-hangzhou.aliyuncs.com/bucket/dir/a%20b?"));
+        let url = url::Url::parse(&signed_url).unwrap();
+        let params: BTreeMap<_, _> = url.query_pairs().collect();
+        assert_eq!(params["response-content-disposition"], "attachment");
+        assert_eq!(params["x-oss-expires"], "3600");
+        assert!(params["x-oss-credential"].starts_with("id/"));
+        assert_eq!(params["x-oss-signature"].len(), 64);
+
+        let week = Duration::from_secs(604800);
+        assert!(oss_instance
+            .get_signed_url_v4("object", Method::GET, week, None, None)
+            .await
+            .is_ok());
+        let err = oss_instance
+            .get_signed_url_v4(
+                "object",
+                Method::GET,
+                week + Duration::from_secs(1),
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().starts_with("invalid configuration"));
+    }
+
+    // Hands out a fresh key pair on every call, like a provider behind
+    // rotating STS credentials.
+    struct RotatingProvider(std::sync::atomic::AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl CredentialsProvider for RotatingProvider {
+        async fn credentials(&self) -> std::result::Result<Credentials, crate::CredentialsError> {
+            let n = self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            Ok(Credentials::new_with_token(
+                format!("key-{}", n),
+                "secret",
+                format!("token-{}", n),
+                None,
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn credentials_provider_test() {
+        let server = crate::test_util::MockServer::start(|_| {
+            crate::test_util::MockResponse::ok("").header("etag", "\"mock\"")
+        })
+        .await;
+        let mut oss_instance = OSSClient::with_credentials_provider(
+            reqwest_client(),
+            "cn-hangzhou",
+            "http",
+            "bucket",
+            RotatingProvider(Default::default()),
+        );
+        oss_instance.region = Region::custom("cn-hangzhou", server.endpoint());
+        oss_instance.set_addressing_style(AddressingStyle::Path);
+
+        let resp = oss_instance
+            .sign_and_dispatch(oss_instance.put_request(FILE_NAME, BUF.to_vec().into_boxed_slice()))
+            .await
+            .unwrap();
+        assert_eq!(resp.headers["etag"], "\"mock\"");
+        let mut rqst = oss_instance.get_request(None);
+        rqst.add_params("prefix", "rust_oss_sdk");
+        oss_instance.sign_and_dispatch(rqst).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, format!("/bucket/{}", FILE_NAME));
+        assert_eq!(requests[0].body, BUF);
+        assert!(requests[0]
+            .header("authorization")
+            .unwrap()
+            .starts_with("OSS key-1:"));
+        assert_eq!(requests[0].header("x-oss-security-token"), Some("token-1"));
+        assert_eq!(requests[1].param("prefix").as_deref(), Some("rust_oss_sdk"));
+        assert!(requests[1]
+            .header("authorization")
+            .unwrap()
+            .starts_with("OSS key-2:"));
+        assert_eq!(requests[1].header("x-oss-security-token"), Some("token-2"));
+    }
+
+    fn retry_client(server: &crate::test_util::MockServer) -> OssClient {
+        let mut oss_instance = crate::test_util::mock_client(server);
+        oss_instance.set_retry_policy(RetryPolicy {
+            base_delay: Duration::from_millis(1),
+            ..Default::default()
+        });
+        oss_instance
+    }
+
+    #[tokio::test]
+    async fn retry_test() {
+        use crate::test_util::{MockResponse, MockServer};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = count.clone();
+        let server = MockServer::start(move |_| match counter.fetch_add(1, Ordering::SeqCst) {
+            0 => MockResponse::status(503),
+            1 => MockResponse::status(403)
+                .body("<Error><Code>RequestTimeTooSkewed</Code><Message>skewed</Message></Error>"),
+            _ => MockResponse::ok("done"),
+        })
+        .await;
+        let oss_instance = retry_client(&server);
+
+        let rqst = oss_instance.put_request(FILE_NAME, BUF.to_vec().into_boxed_slice());
+        let resp = oss_instance.send(rqst).await.unwrap();
+        assert_eq!(&resp.collect().await.unwrap()[..], b"done");
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        for rqst in requests.iter() {
+            assert_eq!(rqst.body, BUF);
+            assert!(rqst.header("authorization").is_some());
+            assert!(rqst.header("date").is_some());
+        }
+
+        // Out of attempts, the last response is returned
+        count.store(0, Ordering::SeqCst);
+        let mut oss_instance = retry_client(&server);
+        oss_instance.set_retry_policy(RetryPolicy {
+            max_attempts: 2,
+            base_delay: Duration::from_millis(1),
+            ..Default::default()
+        });
+        let err = oss_instance
+            .send(oss_instance.get_request(None))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Some("RequestTimeTooSkewed"));
+        assert_eq!(server.requests().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn no_retry_test() {
+        use crate::{
+            test_util::{MockResponse, MockServer},
+            ByteStream,
+        };
+
+        let server = MockServer::start(|_| MockResponse::status(503)).await;
+        let mut oss_instance = retry_client(&server);
+
+        // A stream cannot be sent twice
+        let stream = ByteStream::from_async_read(std::io::Cursor::new(BUF), None);
+        let rqst = oss_instance.put_request(FILE_NAME, stream);
+        let err = oss_instance.send(rqst).await.unwrap_err();
+        assert_eq!(err.status(), Some(http::StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(server.requests().len(), 1);
+
+        // Nor is a request retried past its deadline
+        oss_instance.set_retry_policy(RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_secs(1),
+            deadline: Some(Duration::from_millis(40)),
+            ..Default::default()
+        });
+        let started = Instant::now();
+        let result = oss_instance.send(oss_instance.get_request(None)).await;
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_millis(500));
+        assert!(server.requests().len() < 10);
+
+        // Nor are client errors
+        let server = MockServer::start(|_| MockResponse::status(404)).await;
+        let oss_instance = retry_client(&server);
+        let err = oss_instance
+            .send(oss_instance.get_request(None))
+            .await
+            .unwrap_err();
+        assert!(err.is_not_found());
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn transport_retry_test() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Closes every connection without answering
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = listener.local_addr().unwrap().to_string();
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let counter = accepted.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                drop(socket);
+            }
+        });
+        let mut oss_instance =
+            OSSClient::new_with_reqwest("cn-hangzhou", "http", "bucket", "ak", "sk");
+        oss_instance.region = Region::custom("cn-hangzhou", endpoint);
+        oss_instance.set_addressing_style(AddressingStyle::Path);
+        oss_instance.set_retry_policy(RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            ..Default::default()
+        });
+        let attempts = |rqst| async {
+            accepted.store(0, Ordering::SeqCst);
+            assert!(oss_instance.send(rqst).await.is_err());
+            accepted.load(Ordering::SeqCst)
+        };
+
+        assert_eq!(attempts(oss_instance.get_request(FILE_NAME)).await, 3);
+        // A POST may have been applied before the connection dropped
+        let rqst = oss_instance.post_request(FILE_NAME, BUF.to_vec());
+        assert_eq!(attempts(rqst).await, 1);
+        let mut rqst = oss_instance.post_request(FILE_NAME, BUF.to_vec());
+        rqst.set_idempotent(true);
+        assert_eq!(attempts(rqst).await, 3);
+
+        // Neither is a request the HTTP client cannot even build
+        let e = reqwest::Client::new()
+            .get("http://[::1")
+            .build()
+            .unwrap_err();
+        assert!(!Error::from(HttpError::from(e)).is_retryable());
+    }
+
+    // Answers every connection with the headers of a 10 bytes body, of which
+    // it only ever sends `sent`, or with nothing at all if `None`.
+    async fn stalling_client(sent: Option<&'static [u8]>) -> OssClient {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = [0; 4096];
+                    let _ = socket.read(&mut buf).await;
+                    if let Some(sent) = sent {
+                        let _ = socket
+                            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 10\r\n\r\n")
+                            .await;
+                        let _ = socket.write_all(sent).await;
+                    }
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                });
+            }
+        });
+        let mut oss_instance =
+            OSSClient::new_with_reqwest("cn-hangzhou", "http", "bucket", "ak", "sk");
+        oss_instance.region = Region::custom("cn-hangzhou", endpoint);
+        oss_instance.set_addressing_style(AddressingStyle::Path);
+        oss_instance.set_retry_policy(RetryPolicy::none());
+        oss_instance
+    }
+
+    #[tokio::test]
+    async fn request_timeout_test() {
+        let mut oss_instance = stalling_client(None).await;
+        oss_instance.set_timeouts(Timeouts {
+            request: Some(Duration::from_millis(50)),
+            ..Default::default()
+        });
+        let err = oss_instance
+            .send(oss_instance.get_request(FILE_NAME))
+            .await
+            .unwrap_err();
+        assert!(err.is_timeout());
+
+        // Overridden per request
+        oss_instance.set_timeouts(Timeouts::default());
+        let mut rqst = oss_instance.get_request(FILE_NAME);
+        rqst.set_timeout(Duration::from_millis(50));
+        let err = oss_instance.send(rqst).await.unwrap_err();
+        assert!(err.is_timeout());
+    }
+
+    #[tokio::test]
+    async fn read_timeout_test() {
+        let mut oss_instance = stalling_client(Some(b"0123")).await;
+        let mut rqst = oss_instance.get_request(FILE_NAME);
+        rqst.set_read_timeout(Duration::from_millis(50));
+        let resp = oss_instance.send(rqst).await.unwrap();
+        let err = Error::from(resp.collect().await.unwrap_err());
+        assert!(err.is_timeout());
+
+        let mut writer = Vec::new();
+        oss_instance.set_timeouts(Timeouts {
+            read: Some(Duration::from_millis(50)),
+            ..Default::default()
+        });
+        let err = oss_instance
+            .get_object_to_writer(crate::GetObjectRequest::new(FILE_NAME), &mut writer)
+            .await
+            .unwrap_err();
+        assert!(err.is_timeout());
+        assert_eq!(writer, b"0123");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs OSS_BUCKET, OSS_KEY_ID and OSS_KEY_SECRET of a live bucket"]
     async fn smoke_test() {
         let bucket = std::env::var("OSS_BUCKET").unwrap();
         let access_key_id = std::env::var("OSS_KEY_ID").unwrap();
@@ -236,38 +852,28 @@ mod tests {
         println!("{:?}", ret);
         assert!(ret.is_ok() && ret.unwrap().status.is_success());
 
-        let mut rqst = oss_instance.get_request(None);
-        rqst.add_params("prefix", "rust_oss_sdk");
-        let ret = oss_instance.sign_and_dispatch(rqst).await;
-        assert!(ret.is_ok() && ret.unwrap().status.is_success());
+        let listed = oss_instance
+            .list_objects_v2(ListObjectsV2Options {
+                prefix: Some("rust_oss_sdk".to_owned()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(listed.contents.iter().any(|object| object.key == FILE_NAME));
 
         let rqst = oss_instance.get_request(FILE_NAME);
         let ret = oss_instance.sign_and_dispatch(rqst).await;
-        assert!(ret.is_ok() && ret.unwrap().body == Box::pin(BUF));
+        assert_eq!(ret.unwrap().collect().await.unwrap(), BUF);
 
-        let rqst = oss_instance.head_request(FILE_NAME);
-        let ret = oss_instance.sign_and_dispatch(rqst).await;
-        assert!(ret.is_ok() && ret.unwrap().headers.contains_key("x-oss-meta-test-key"));
+        let meta = oss_instance.head_object(FILE_NAME).await.unwrap();
+        assert_eq!(meta.metadata["test-key"], "test-val");
 
         let rqst = oss_instance.del_request(FILE_NAME);
         let ret = oss_instance.sign_and_dispatch(rqst).await;
         assert!(ret.is_ok() && ret.unwrap().status.is_success());
 
         let rqst = oss_instance.get_request(FILE_NAME);
-        let ret = oss_instance.sign_and_dispatch(rqst).await;
-        assert!(ret.is_ok() && ret.unwrap().status.is_client_error());
-    }
-}
-#[inline]
-fn get_oss_subresource_signed_str(bucket: &str, object: &str, oss_resources: &str) -> String {
-    let oss_resources = if oss_resources != "" {
-        String::from("?") + oss_resources
-    } else {
-        String::new()
-    };
-    if bucket == "" {
-        format!("/{}{}", bucket, oss_resources)
-    } else {
-        format!("/{}/{}{}", bucket, object, oss_resources)
+        let err = oss_instance.send(rqst).await.unwrap_err();
+        assert_eq!(err.code(), Some("NoSuchKey"));
     }
 }
@@ -1,4 +1,7 @@
 use once_cell::sync::OnceCell;
+
+use crate::Timeouts;
+
 pub const OSS_PREFIX: &str = "x-oss-meta-";
 pub const OSS_CANONICALIZED_PREFIX: &str = "x-oss-";
 
@@ -8,5 +11,20 @@ pub(crate) const CONTENT_MD5: &str = "Content-MD5";
 // Reusable Lazy Initialized Global reqwest::Client
 static REQWEST_CLIENT: OnceCell<reqwest::Client> = OnceCell::new();
 pub(crate) fn reqwest_client() -> reqwest::Client {
-    REQWEST_CLIENT.get_or_init(reqwest::Client::new).clone()
+    REQWEST_CLIENT
+        .get_or_init(|| {
+            reqwest_client_builder(&Timeouts::default())
+                .build()
+                .unwrap_or_default()
+        })
+        .clone()
+}
+/// Builder of a reqwest client connecting within `timeouts.connect`. Request
+/// and read timeouts are applied per request.
+pub(crate) fn reqwest_client_builder(timeouts: &Timeouts) -> reqwest::ClientBuilder {
+    let mut builder = reqwest::Client::builder();
+    if let Some(connect) = timeouts.connect {
+        builder = builder.connect_timeout(connect);
+    }
+    builder
 }
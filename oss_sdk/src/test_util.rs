@@ -0,0 +1,199 @@
+//! A minimal HTTP/1.1 server standing in for OSS, STS or the ECS metadata
+//! service in tests.
+
+use std::{
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use crate::{AddressingStyle, OSSClient, OssClient, Region};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MockRequest {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    /// Lowercased names
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+    /// Decoded query params
+    pub fn params(&self) -> Vec<(String, String)> {
+        url::form_urlencoded::parse(self.query.as_bytes())
+            .into_owned()
+            .collect()
+    }
+    pub fn param(&self, name: &str) -> Option<String> {
+        self.params()
+            .into_iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+    pub fn ok<B: Into<Vec<u8>>>(body: B) -> Self {
+        Self::status(200).body(body)
+    }
+    pub fn body<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
+        self.body = body.into();
+        self
+    }
+    pub fn header<K: Into<String>, V: Into<String>>(mut self, k: K, v: V) -> Self {
+        self.headers.push((k.into(), v.into()));
+        self
+    }
+}
+
+/// Client of `bucket` on `server`, with path-style addressing.
+pub(crate) fn mock_client(server: &MockServer) -> OssClient {
+    let mut oss_instance = OSSClient::new_with_reqwest("cn-hangzhou", "http", "bucket", "ak", "sk");
+    oss_instance.region = Region::custom("cn-hangzhou", server.endpoint());
+    oss_instance.set_addressing_style(AddressingStyle::Path);
+    oss_instance
+}
+
+/// `name` in the temporary directory, unique to this test process. The
+/// extension of `name`, if any, is kept.
+pub(crate) fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("oss_sdk_{}_{}", std::process::id(), name))
+}
+
+type Handler = dyn Fn(&MockRequest) -> MockResponse + Send + Sync;
+
+pub(crate) struct MockServer {
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl MockServer {
+    pub async fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    let _ = serve(stream, handler, recorded).await;
+                });
+            }
+        });
+        Self { addr, requests }
+    }
+    /// `host:port` of the server
+    pub fn endpoint(&self) -> String {
+        self.addr.to_string()
+    }
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+    /// Every request served so far
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn serve(
+    stream: TcpStream,
+    handler: Arc<Handler>,
+    recorded: Arc<Mutex<Vec<MockRequest>>>,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_owned();
+    let target = parts.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut rqst = MockRequest {
+        method,
+        path: path.to_owned(),
+        query: query.to_owned(),
+        ..Default::default()
+    };
+    loop {
+        line.clear();
+        reader.read_line(&mut line).await?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((k, v)) = header.split_once(':') {
+            rqst.headers
+                .push((k.trim().to_ascii_lowercase(), v.trim().to_owned()));
+        }
+    }
+    if rqst.header("transfer-encoding") == Some("chunked") {
+        loop {
+            line.clear();
+            reader.read_line(&mut line).await?;
+            let size = usize::from_str_radix(line.trim(), 16).unwrap_or_default();
+            let mut chunk = vec![0; size + 2];
+            reader.read_exact(&mut chunk).await?;
+            if size == 0 {
+                break;
+            }
+            rqst.body.extend_from_slice(&chunk[..size]);
+        }
+    } else if let Some(len) = rqst.header("content-length") {
+        rqst.body = vec![0; len.parse().unwrap_or_default()];
+        reader.read_exact(&mut rqst.body).await?;
+    }
+
+    let resp = handler(&rqst);
+    let is_head = rqst.method == "HEAD";
+    recorded.lock().unwrap().push(rqst);
+    let mut head = format!("HTTP/1.1 {} Mock\r\n", resp.status);
+    for (k, v) in resp.headers.iter() {
+        head += &format!("{}: {}\r\n", k, v);
+    }
+    if !resp
+        .headers
+        .iter()
+        .any(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+    {
+        head += &format!("content-length: {}\r\n", resp.body.len());
+    }
+    head += "connection: close\r\n\r\n";
+    let mut stream = reader.into_inner();
+    stream.write_all(head.as_bytes()).await?;
+    if !is_head {
+        stream.write_all(&resp.body).await?;
+    }
+    stream.shutdown().await
+}
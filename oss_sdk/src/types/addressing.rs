@@ -0,0 +1,12 @@
+/// How the bucket is addressed in request urls.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AddressingStyle {
+    /// `bucket.endpoint/object`, what OSS itself expects
+    #[default]
+    VirtualHosted,
+    /// `endpoint/bucket/object`, for local emulators and proxies that cannot
+    /// route on the host name
+    Path,
+    /// `endpoint/object`, where the endpoint is a CNAME domain bound to the bucket
+    Cname,
+}
@@ -1,8 +1,12 @@
 use std::{error::Error as StdError, fmt};
 
-use http::header::{InvalidHeaderName, InvalidHeaderValue};
+use http::{
+    header::{InvalidHeaderName, InvalidHeaderValue},
+    StatusCode,
+};
+use serde::Deserialize;
 
-use crate::HttpError;
+use crate::{xml, CredentialsError, HttpError, HttpResponse, ParseRegionError, ParseSchemaError};
 
 pub(crate) type Result<T> = std::result::Result<T, Error>;
 
@@ -17,6 +21,12 @@ pub struct Error {
 pub(crate) enum Kind {
     Http,
     InvalidHeader,
+    Credentials,
+    Io,
+    Xml,
+    Service,
+    Timeout,
+    Config,
 }
 impl Error {
     pub(crate) fn new<E>(kind: Kind, err: E) -> Self
@@ -28,11 +38,180 @@ impl Error {
             source: Some(err.into()),
         }
     }
+    /// An invalid client setting.
+    pub(crate) fn config<E: Into<BoxedError>>(e: E) -> Self {
+        Self::new(Kind::Config, e)
+    }
+    /// A truncated page of a listing whose next marker would only fetch the
+    /// same page again.
+    pub(crate) fn stuck_listing<M: fmt::Debug>(marker: M) -> Self {
+        Self::new(
+            Kind::Service,
+            format!("truncated listing does not advance past {:?}", marker),
+        )
+    }
+    /// The error OSS answered with, for non-2xx responses.
+    pub fn service_error(&self) -> Option<&ServiceError> {
+        self.source
+            .as_ref()
+            .and_then(|e| e.downcast_ref::<ServiceError>())
+    }
+    /// Status code of a non-2xx response.
+    pub fn status(&self) -> Option<StatusCode> {
+        self.service_error().map(|e| e.status)
+    }
+    /// OSS error code, e.g. `NoSuchKey` or `AccessDenied`.
+    pub fn code(&self) -> Option<&str> {
+        self.service_error().map(|e| e.code.as_str())
+    }
+    /// The bucket, object or multipart upload does not exist.
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(StatusCode::NOT_FOUND)
+    }
+    /// A connect, request or read timeout expired.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self.kind, Kind::Timeout)
+    }
+    /// Whether sending the request again may succeed: network failures,
+    /// throttling and server errors.
+    pub(crate) fn is_retryable(&self) -> bool {
+        match self.kind {
+            Kind::Http => self
+                .source
+                .as_ref()
+                .and_then(|e| e.downcast_ref::<HttpError>())
+                .is_some_and(HttpError::is_retryable),
+            Kind::Service => self.status().is_some_and(|status| {
+                status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+            }),
+            Kind::Timeout => true,
+            _ => false,
+        }
+    }
+}
+
+/// Error response of OSS, e.g.
+/// ```xml
+/// <Error>
+///   <Code>NoSuchKey</Code>
+///   <Message>The specified key does not exist.</Message>
+///   <RequestId>5C3D9175B6FC201293AD****</RequestId>
+///   <HostId>bucket.oss-cn-hangzhou.aliyuncs.com</HostId>
+///   <EC>0026-00000001</EC>
+/// </Error>
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ServiceError {
+    #[serde(skip)]
+    pub status: StatusCode,
+    /// Empty when the response did not carry an OSS error, e.g. from a proxy.
+    pub code: String,
+    /// The response body when it did not carry an OSS error.
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub request_id: String,
+    #[serde(default)]
+    pub host_id: String,
+    /// Detailed error code, to look up in the OSS documentation.
+    #[serde(rename = "EC")]
+    pub ec: Option<String>,
+    /// What the server signed, to compare with the client's on
+    /// `SignatureDoesNotMatch`.
+    pub string_to_sign: Option<String>,
+    /// The canonical request of V4 signatures, on `SignatureDoesNotMatch`.
+    pub canonical_request: Option<String>,
+}
+
+impl ServiceError {
+    /// Reads the error out of a non-2xx response. HEAD responses have no
+    /// body and carry it base64-encoded in `x-oss-err` instead.
+    pub(crate) async fn from_response(resp: HttpResponse) -> Result<ServiceError> {
+        let status = resp.status;
+        let request_id = resp.request_id().to_owned();
+        let ec = resp.header("x-oss-ec").map(str::to_owned);
+        let err_header = resp
+            .header("x-oss-err")
+            .and_then(|e| base64::decode(e).ok());
+        let mut body = resp.collect().await?.to_vec();
+        if body.is_empty() {
+            body = err_header.unwrap_or_default();
+        }
+        let mut err = xml::from_slice::<ServiceError>(&body).unwrap_or_else(|_| ServiceError {
+            message: String::from_utf8_lossy(&body).into_owned(),
+            ..Default::default()
+        });
+        err.status = status;
+        if err.request_id.is_empty() {
+            err.request_id = request_id;
+        }
+        err.ec = err.ec.or(ec);
+        Ok(err)
+    }
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.status)?;
+        if !self.code.is_empty() {
+            write!(f, " {}", self.code)?;
+        }
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+        if let Some(ref ec) = self.ec {
+            write!(f, " (EC {})", ec)?;
+        }
+        if !self.request_id.is_empty() {
+            write!(f, " (request id {})", self.request_id)?;
+        }
+        Ok(())
+    }
 }
+impl StdError for ServiceError {}
 
 impl From<HttpError> for Error {
     fn from(e: HttpError) -> Error {
-        Error::new(Kind::Http, e)
+        if e.is_timeout() {
+            Error::new(Kind::Timeout, e)
+        } else {
+            Error::new(Kind::Http, e)
+        }
+    }
+}
+impl From<ServiceError> for Error {
+    fn from(e: ServiceError) -> Error {
+        Error::new(Kind::Service, e)
+    }
+}
+impl From<CredentialsError> for Error {
+    fn from(e: CredentialsError) -> Error {
+        Error::new(Kind::Credentials, e)
+    }
+}
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Error {
+        if e.kind() == std::io::ErrorKind::TimedOut {
+            Error::new(Kind::Timeout, e)
+        } else {
+            Error::new(Kind::Io, e)
+        }
+    }
+}
+impl From<quick_xml::DeError> for Error {
+    fn from(e: quick_xml::DeError) -> Error {
+        Error::new(Kind::Xml, e)
+    }
+}
+impl From<ParseRegionError> for Error {
+    fn from(e: ParseRegionError) -> Error {
+        Error::new(Kind::Config, e)
+    }
+}
+impl From<ParseSchemaError> for Error {
+    fn from(e: ParseSchemaError) -> Error {
+        Error::new(Kind::Config, e)
     }
 }
 impl From<InvalidHeaderName> for Error {
@@ -59,8 +238,14 @@ impl fmt::Debug for Error {
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.kind {
-            Kind::Http => f.write_str("request or response body error")?,
-            _ => unimplemented!(),
+            Kind::Http => f.write_str("HTTP error")?,
+            Kind::InvalidHeader => f.write_str("invalid header")?,
+            Kind::Credentials => f.write_str("failed to load credentials")?,
+            Kind::Io => f.write_str("I/O error")?,
+            Kind::Xml => f.write_str("invalid XML")?,
+            Kind::Service => f.write_str("OSS error")?,
+            Kind::Timeout => f.write_str("timed out")?,
+            Kind::Config => f.write_str("invalid configuration")?,
         };
         if let Some(ref e) = self.source {
             write!(f, ": {}", e)?;
@@ -73,6 +258,91 @@ impl std::error::Error for Error {
         self.source.as_ref().map(|e| &**e as _)
     }
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{mock_client, MockRequest, MockResponse, MockServer};
+
+    const NO_SUCH_KEY: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+        <Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message>\
+        <RequestId>5C3D9175B6FC201293AD****</RequestId><HostId>bucket.oss-cn-hangzhou.aliyuncs.com</HostId>\
+        <Key>missing</Key><EC>0026-00000001</EC></Error>";
+
+    fn error_server(rqst: &MockRequest) -> MockResponse {
+        match (rqst.method.as_str(), rqst.path.as_str()) {
+            ("GET", "/bucket/signed") => MockResponse::status(403).body(
+                "<Error><Code>SignatureDoesNotMatch</Code>\
+                 <Message>The request signature we calculated does not match the signature you provided.</Message>\
+                 <RequestId>id</RequestId><HostId>host</HostId>\
+                 <StringToSign>GET\n\n\nDate\n/bucket/signed</StringToSign>\
+                 <EC>0002-00000040</EC></Error>",
+            ),
+            ("HEAD", _) => MockResponse::status(404)
+                .header("x-oss-request-id", "5C3D9175B6FC201293AD****")
+                .header("x-oss-ec", "0026-00000001")
+                .header("x-oss-err", base64::encode(NO_SUCH_KEY)),
+            _ => MockResponse::status(502)
+                .header("x-oss-request-id", "proxied")
+                .body("<html>Bad Gateway</html>"),
+        }
+    }
+
+    #[tokio::test]
+    async fn service_error_test() {
+        let server = MockServer::start(error_server).await;
+        let oss_instance = mock_client(&server);
+
+        let err = oss_instance
+            .send(oss_instance.get_request("signed"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::FORBIDDEN));
+        assert_eq!(err.code(), Some("SignatureDoesNotMatch"));
+        assert!(!err.is_not_found());
+        assert!(!err.is_retryable());
+        let service_error = err.service_error().unwrap();
+        assert_eq!(service_error.request_id, "id");
+        assert_eq!(service_error.host_id, "host");
+        assert_eq!(service_error.ec.as_deref(), Some("0002-00000040"));
+        assert_eq!(
+            service_error.string_to_sign.as_deref(),
+            Some("GET\n\n\nDate\n/bucket/signed")
+        );
+        assert_eq!(
+            err.to_string(),
+            "OSS error: 403 Forbidden SignatureDoesNotMatch: The request signature we \
+             calculated does not match the signature you provided. (EC 0002-00000040) \
+             (request id id)"
+        );
+
+        // HEAD responses carry the error in a header
+        let err = oss_instance.head_object("missing").await.unwrap_err();
+        assert!(err.is_not_found());
+        assert_eq!(
+            err.service_error(),
+            Some(&ServiceError {
+                status: StatusCode::NOT_FOUND,
+                code: "NoSuchKey".to_owned(),
+                message: "The specified key does not exist.".to_owned(),
+                request_id: "5C3D9175B6FC201293AD****".to_owned(),
+                host_id: "bucket.oss-cn-hangzhou.aliyuncs.com".to_owned(),
+                ec: Some("0026-00000001".to_owned()),
+                ..Default::default()
+            })
+        );
+
+        let err = oss_instance
+            .send(oss_instance.get_request("proxied"))
+            .await
+            .unwrap_err();
+        assert!(err.is_retryable());
+        let service_error = err.service_error().unwrap();
+        assert_eq!(service_error.code, "");
+        assert_eq!(service_error.message, "<html>Bad Gateway</html>");
+        assert_eq!(service_error.request_id, "proxied");
+    }
+}
+
 // impl From<QxmlError> for Error {
 //     fn from(e: QxmlError) -> Error {
 //         Error::Qxml(e)
@@ -1,8 +1,23 @@
+mod addressing;
+mod errors;
+mod object_attributes;
 mod regions;
+mod requests;
+mod retry_policy;
 mod schema;
-mod errors;
+mod signature_version;
+mod stream;
+mod timeouts;
 
+pub use addressing::*;
+pub use errors::{Error, ServiceError};
+pub use object_attributes::*;
 pub use regions::*;
+pub use requests::*;
+pub use retry_policy::RetryPolicy;
 pub use schema::*;
+pub use signature_version::*;
+pub use stream::ByteStream;
+pub use timeouts::Timeouts;
 
-pub(crate) use errors::{Error, Result};
\ No newline at end of file
+pub(crate) use errors::Result;
@@ -0,0 +1,162 @@
+use std::{
+    error::Error as StdError,
+    fmt::{Display, Error as FmtError, Formatter},
+    str::FromStr,
+};
+
+/// Canned ACL of a bucket or an object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Display)]
+pub enum Acl {
+    /// Objects only: inherit the ACL of the bucket
+    #[display(fmt = "default")]
+    Default,
+    #[display(fmt = "private")]
+    Private,
+    #[display(fmt = "public-read")]
+    PublicRead,
+    #[display(fmt = "public-read-write")]
+    PublicReadWrite,
+}
+impl FromStr for Acl {
+    type Err = ParseAttributeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(Acl::Default),
+            "private" => Ok(Acl::Private),
+            "public-read" => Ok(Acl::PublicRead),
+            "public-read-write" => Ok(Acl::PublicReadWrite),
+            _ => Err(ParseAttributeError::new("ACL", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Display)]
+pub enum StorageClass {
+    Standard,
+    #[display(fmt = "IA")]
+    InfrequentAccess,
+    Archive,
+    ColdArchive,
+    DeepColdArchive,
+}
+impl FromStr for StorageClass {
+    type Err = ParseAttributeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Standard" => Ok(StorageClass::Standard),
+            "IA" => Ok(StorageClass::InfrequentAccess),
+            "Archive" => Ok(StorageClass::Archive),
+            "ColdArchive" => Ok(StorageClass::ColdArchive),
+            "DeepColdArchive" => Ok(StorageClass::DeepColdArchive),
+            _ => Err(ParseAttributeError::new("storage class", s)),
+        }
+    }
+}
+
+/// How a bucket stores its data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Display)]
+pub enum DataRedundancyType {
+    /// Locally redundant, within a single zone
+    #[display(fmt = "LRS")]
+    Lrs,
+    /// Zone redundant, across the zones of the region
+    #[display(fmt = "ZRS")]
+    Zrs,
+}
+impl FromStr for DataRedundancyType {
+    type Err = ParseAttributeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "LRS" => Ok(DataRedundancyType::Lrs),
+            "ZRS" => Ok(DataRedundancyType::Zrs),
+            _ => Err(ParseAttributeError::new("data redundancy type", s)),
+        }
+    }
+}
+
+/// How an object was created, `x-oss-object-type`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Display)]
+pub enum ObjectType {
+    /// Uploaded by `PutObject`
+    Normal,
+    /// Created by `AppendObject`
+    Appendable,
+    /// Assembled by a multipart upload
+    Multipart,
+    Symlink,
+}
+impl FromStr for ObjectType {
+    type Err = ParseAttributeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Normal" => Ok(ObjectType::Normal),
+            "Appendable" => Ok(ObjectType::Appendable),
+            "Multipart" => Ok(ObjectType::Multipart),
+            "Symlink" => Ok(ObjectType::Symlink),
+            _ => Err(ParseAttributeError::new("object type", s)),
+        }
+    }
+}
+
+/// Server-side encryption of an object at rest.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ServerSideEncryption {
+    /// Keys fully managed by OSS
+    Aes256,
+    /// Keys managed by KMS, the default CMK of OSS when `key_id` is `None`
+    Kms { key_id: Option<String> },
+    /// Chinese SM4 algorithm, keys fully managed by OSS
+    Sm4,
+}
+impl ServerSideEncryption {
+    /// Value of the `x-oss-server-side-encryption` header.
+    pub fn algorithm(&self) -> &'static str {
+        match self {
+            ServerSideEncryption::Aes256 => "AES256",
+            ServerSideEncryption::Kms { .. } => "KMS",
+            ServerSideEncryption::Sm4 => "SM4",
+        }
+    }
+    pub fn key_id(&self) -> Option<&str> {
+        match self {
+            ServerSideEncryption::Kms { key_id } => key_id.as_deref(),
+            _ => None,
+        }
+    }
+    /// From the `x-oss-server-side-encryption` and
+    /// `x-oss-server-side-encryption-key-id` headers.
+    pub fn new(algorithm: &str, key_id: Option<String>) -> Result<Self, ParseAttributeError> {
+        match algorithm {
+            "AES256" => Ok(ServerSideEncryption::Aes256),
+            "KMS" => Ok(ServerSideEncryption::Kms { key_id }),
+            "SM4" => Ok(ServerSideEncryption::Sm4),
+            _ => Err(ParseAttributeError::new(
+                "server-side encryption",
+                algorithm,
+            )),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseAttributeError {
+    message: String,
+}
+impl ParseAttributeError {
+    pub fn new(attribute: &str, input: &str) -> Self {
+        ParseAttributeError {
+            message: format!("Invalid OSS {}: {}", attribute, input),
+        }
+    }
+}
+
+impl StdError for ParseAttributeError {}
+impl Display for ParseAttributeError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        write!(f, "{}", self.message)
+    }
+}
@@ -6,73 +6,383 @@ use std::{
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Region {
-    /// 华东-杭州
+    /// 华东1-杭州
     HangZhou,
-    /// 华东-上海
+    /// 华东2-上海
     ShangHai,
-    /// 华北-青岛
+    /// 华东5-南京（本地地域）
+    NanJing,
+    /// 华东6-福州（本地地域）
+    FuZhou,
+    /// 华北1-青岛
     QingDao,
-    /// 华北-北京
+    /// 华北2-北京
     BeiJing,
-    /// 华北-张家口
+    /// 华北3-张家口
     ZhangJiaKou,
-    /// 华北-呼和浩特
+    /// 华北5-呼和浩特
     HuHeHaoTe,
-    /// 华北-乌兰察布
+    /// 华北6-乌兰察布
     WuLanChaBu,
-    /// 华南-深圳
+    /// 华南1-深圳
     ShenZhen,
-    /// 华南-河源
+    /// 华南2-河源
     HeYuan,
-    /// 华南-广州
+    /// 华南3-广州
     GuangZhou,
-    /// 西南-成都
+    /// 西南1-成都
     ChengDu,
-    /// Region that covers China
+    /// 中国香港
     HongKong,
-    /// Region that covers the Western part of the United States
+    /// Region that covers the Western part of the United States (Silicon Valley)
     UsWest,
-    /// Region that covers the Eastern part of the United States
+    /// Region that covers the Eastern part of the United States (Virginia)
     UsEast,
-    /// Region that covers the South-Eastern part of Asia Pacific
+    /// Region that covers the South-Eastern part of Asia Pacific (Singapore)
     ApSoutheast1,
-    /// Region that covers the South-Eastern part of Asia Pacific
+    /// Region that covers the South-Eastern part of Asia Pacific (Sydney)
     ApSoutheast2,
-    /// Region that covers the South-Eastern part of Asia Pacific
+    /// Region that covers the South-Eastern part of Asia Pacific (Kuala Lumpur)
     ApSoutheast3,
-    /// Region that covers the South-Eastern part of Asia Pacific
+    /// Region that covers the South-Eastern part of Asia Pacific (Jakarta)
     ApSoutheast5,
-    /// Region that covers the North-Eastern part of Asia Pacific
+    /// Region that covers the South-Eastern part of Asia Pacific (Manila)
+    ApSoutheast6,
+    /// Region that covers the South-Eastern part of Asia Pacific (Bangkok)
+    ApSoutheast7,
+    /// Region that covers the North-Eastern part of Asia Pacific (Tokyo)
     ApNortheast,
-    /// Region that covers the Southern part of Asia Pacific
+    /// Region that covers the North-Eastern part of Asia Pacific (Seoul)
+    ApNortheast2,
+    /// Region that covers the Southern part of Asia Pacific (Mumbai)
     ApSouth,
-    /// Region that covers Central Europe
+    /// Region that covers Central Europe (Frankfurt)
     EuCentral,
-    /// Region that covers Western Europe
+    /// Region that covers Western Europe (London)
     EuWest,
-    /// Region that covers Eastern Europe
-    EuEast,
-    /// Region that covers the Eastern part of Middle East
+    /// Region that covers the Eastern part of Middle East (Dubai)
     MeEast,
-    //TODO below:
-    // Custom {
-    //     name: String,
-    //     endpoint: String,
-    // },
+    /// Any other OSS compatible service: Apsara Stack (private cloud) OSS,
+    /// a CNAME domain bound to a bucket or a local stand-in server.
+    /// `endpoint` is a `host[:port]`, `name` the region id used for signing.
+    Custom { name: String, endpoint: String },
+}
+
+/// Which of the endpoints of a region requests are sent to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EndpointType {
+    /// `oss-<region>.aliyuncs.com`, reachable from the internet
+    #[default]
+    Public,
+    /// `oss-<region>-internal.aliyuncs.com`, only reachable from ECS instances
+    /// and VPCs in the same region
+    Internal,
+    /// `oss-accelerate.aliyuncs.com`, global transfer acceleration
+    Accelerate,
+    /// `oss-accelerate-overseas.aliyuncs.com`, transfer acceleration for
+    /// regions outside mainland China
+    AccelerateOverseas,
+}
+
+pub const ACCELERATE_ENDPOINT: &str = "oss-accelerate.aliyuncs.com";
+pub const ACCELERATE_OVERSEAS_ENDPOINT: &str = "oss-accelerate-overseas.aliyuncs.com";
+
+struct RegionInfo {
+    id: &'static str,
+    endpoint: &'static str,
+    internal_endpoint: &'static str,
+    aliases: &'static [&'static str],
 }
 
 impl Region {
-    pub fn name(&self) -> &'static str {
-        match *self {
-            Self::BeiJing => "cn-beijin",
-            _ => unimplemented!(),
+    /// Every region known to the sdk.
+    pub const ALL: [Region; 28] = [
+        Region::HangZhou,
+        Region::ShangHai,
+        Region::NanJing,
+        Region::FuZhou,
+        Region::QingDao,
+        Region::BeiJing,
+        Region::ZhangJiaKou,
+        Region::HuHeHaoTe,
+        Region::WuLanChaBu,
+        Region::ShenZhen,
+        Region::HeYuan,
+        Region::GuangZhou,
+        Region::ChengDu,
+        Region::HongKong,
+        Region::UsWest,
+        Region::UsEast,
+        Region::ApSoutheast1,
+        Region::ApSoutheast2,
+        Region::ApSoutheast3,
+        Region::ApSoutheast5,
+        Region::ApSoutheast6,
+        Region::ApSoutheast7,
+        Region::ApNortheast,
+        Region::ApNortheast2,
+        Region::ApSouth,
+        Region::EuCentral,
+        Region::EuWest,
+        Region::MeEast,
+    ];
+
+    /// Builds a `Region::Custom`, any scheme or trailing `/` of `endpoint` is dropped.
+    pub fn custom<S1, S2>(name: S1, endpoint: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: AsRef<str>,
+    {
+        let endpoint = endpoint.as_ref();
+        let endpoint = endpoint
+            .strip_prefix("https://")
+            .or_else(|| endpoint.strip_prefix("http://"))
+            .unwrap_or(endpoint)
+            .trim_end_matches('/');
+        Region::Custom {
+            name: name.into(),
+            endpoint: endpoint.to_owned(),
+        }
+    }
+    pub fn is_custom(&self) -> bool {
+        matches!(self, Self::Custom { .. })
+    }
+
+    /// Region id, e.g. `cn-hangzhou`
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Custom { name, .. } => name,
+            _ => self.info().id,
+        }
+    }
+    /// Public endpoint, e.g. `oss-cn-hangzhou.aliyuncs.com`
+    pub fn endpoint(&self) -> &str {
+        match self {
+            Self::Custom { endpoint, .. } => endpoint,
+            _ => self.info().endpoint,
+        }
+    }
+    /// VPC endpoint, e.g. `oss-cn-hangzhou-internal.aliyuncs.com`
+    pub fn internal_endpoint(&self) -> &str {
+        match self {
+            Self::Custom { endpoint, .. } => endpoint,
+            _ => self.info().internal_endpoint,
         }
     }
-    pub fn endpoint(&self) -> &'static str {
-        match *self {
-            Self::BeiJing => "oss-cn-beijing.aliyuncs.com",
-            _ => unimplemented!(),
+    /// A custom region always resolves to its own endpoint.
+    pub fn endpoint_of(&self, endpoint_type: EndpointType) -> &str {
+        if let Self::Custom { endpoint, .. } = self {
+            return endpoint;
         }
+        match endpoint_type {
+            EndpointType::Public => self.endpoint(),
+            EndpointType::Internal => self.internal_endpoint(),
+            EndpointType::Accelerate => ACCELERATE_ENDPOINT,
+            EndpointType::AccelerateOverseas => ACCELERATE_OVERSEAS_ENDPOINT,
+        }
+    }
+
+    /// Table of the built-in regions, custom ones carry their own endpoint.
+    fn info(&self) -> RegionInfo {
+        let (id, endpoint, internal_endpoint, aliases): (_, _, _, &'static [&'static str]) =
+            match *self {
+                Self::HangZhou => (
+                    "cn-hangzhou",
+                    "oss-cn-hangzhou.aliyuncs.com",
+                    "oss-cn-hangzhou-internal.aliyuncs.com",
+                    &["杭州", "华东1"],
+                ),
+                Self::ShangHai => (
+                    "cn-shanghai",
+                    "oss-cn-shanghai.aliyuncs.com",
+                    "oss-cn-shanghai-internal.aliyuncs.com",
+                    &["上海", "华东2"],
+                ),
+                Self::NanJing => (
+                    "cn-nanjing",
+                    "oss-cn-nanjing.aliyuncs.com",
+                    "oss-cn-nanjing-internal.aliyuncs.com",
+                    &["南京", "华东5"],
+                ),
+                Self::FuZhou => (
+                    "cn-fuzhou",
+                    "oss-cn-fuzhou.aliyuncs.com",
+                    "oss-cn-fuzhou-internal.aliyuncs.com",
+                    &["福州", "华东6"],
+                ),
+                Self::QingDao => (
+                    "cn-qingdao",
+                    "oss-cn-qingdao.aliyuncs.com",
+                    "oss-cn-qingdao-internal.aliyuncs.com",
+                    &["青岛", "华北1"],
+                ),
+                Self::BeiJing => (
+                    "cn-beijing",
+                    "oss-cn-beijing.aliyuncs.com",
+                    "oss-cn-beijing-internal.aliyuncs.com",
+                    &["北京", "华北2"],
+                ),
+                Self::ZhangJiaKou => (
+                    "cn-zhangjiakou",
+                    "oss-cn-zhangjiakou.aliyuncs.com",
+                    "oss-cn-zhangjiakou-internal.aliyuncs.com",
+                    &["张家口", "华北3"],
+                ),
+                Self::HuHeHaoTe => (
+                    "cn-huhehaote",
+                    "oss-cn-huhehaote.aliyuncs.com",
+                    "oss-cn-huhehaote-internal.aliyuncs.com",
+                    &["呼和浩特", "华北5"],
+                ),
+                Self::WuLanChaBu => (
+                    "cn-wulanchabu",
+                    "oss-cn-wulanchabu.aliyuncs.com",
+                    "oss-cn-wulanchabu-internal.aliyuncs.com",
+                    &["乌兰察布", "华北6"],
+                ),
+                Self::ShenZhen => (
+                    "cn-shenzhen",
+                    "oss-cn-shenzhen.aliyuncs.com",
+                    "oss-cn-shenzhen-internal.aliyuncs.com",
+                    &["深圳", "华南1"],
+                ),
+                Self::HeYuan => (
+                    "cn-heyuan",
+                    "oss-cn-heyuan.aliyuncs.com",
+                    "oss-cn-heyuan-internal.aliyuncs.com",
+                    &["河源", "华南2"],
+                ),
+                Self::GuangZhou => (
+                    "cn-guangzhou",
+                    "oss-cn-guangzhou.aliyuncs.com",
+                    "oss-cn-guangzhou-internal.aliyuncs.com",
+                    &["广州", "华南3"],
+                ),
+                Self::ChengDu => (
+                    "cn-chengdu",
+                    "oss-cn-chengdu.aliyuncs.com",
+                    "oss-cn-chengdu-internal.aliyuncs.com",
+                    &["成都", "西南1"],
+                ),
+                Self::HongKong => (
+                    "cn-hongkong",
+                    "oss-cn-hongkong.aliyuncs.com",
+                    "oss-cn-hongkong-internal.aliyuncs.com",
+                    &["香港", "中国香港"],
+                ),
+                Self::UsWest => (
+                    "us-west-1",
+                    "oss-us-west-1.aliyuncs.com",
+                    "oss-us-west-1-internal.aliyuncs.com",
+                    &["硅谷", "美国西部1"],
+                ),
+                Self::UsEast => (
+                    "us-east-1",
+                    "oss-us-east-1.aliyuncs.com",
+                    "oss-us-east-1-internal.aliyuncs.com",
+                    &["弗吉尼亚", "美国东部1"],
+                ),
+                Self::ApSoutheast1 => (
+                    "ap-southeast-1",
+                    "oss-ap-southeast-1.aliyuncs.com",
+                    "oss-ap-southeast-1-internal.aliyuncs.com",
+                    &["新加坡"],
+                ),
+                Self::ApSoutheast2 => (
+                    "ap-southeast-2",
+                    "oss-ap-southeast-2.aliyuncs.com",
+                    "oss-ap-southeast-2-internal.aliyuncs.com",
+                    &["悉尼"],
+                ),
+                Self::ApSoutheast3 => (
+                    "ap-southeast-3",
+                    "oss-ap-southeast-3.aliyuncs.com",
+                    "oss-ap-southeast-3-internal.aliyuncs.com",
+                    &["吉隆坡"],
+                ),
+                Self::ApSoutheast5 => (
+                    "ap-southeast-5",
+                    "oss-ap-southeast-5.aliyuncs.com",
+                    "oss-ap-southeast-5-internal.aliyuncs.com",
+                    &["雅加达"],
+                ),
+                Self::ApSoutheast6 => (
+                    "ap-southeast-6",
+                    "oss-ap-southeast-6.aliyuncs.com",
+                    "oss-ap-southeast-6-internal.aliyuncs.com",
+                    &["马尼拉"],
+                ),
+                Self::ApSoutheast7 => (
+                    "ap-southeast-7",
+                    "oss-ap-southeast-7.aliyuncs.com",
+                    "oss-ap-southeast-7-internal.aliyuncs.com",
+                    &["曼谷"],
+                ),
+                Self::ApNortheast => (
+                    "ap-northeast-1",
+                    "oss-ap-northeast-1.aliyuncs.com",
+                    "oss-ap-northeast-1-internal.aliyuncs.com",
+                    &["东京", "日本"],
+                ),
+                Self::ApNortheast2 => (
+                    "ap-northeast-2",
+                    "oss-ap-northeast-2.aliyuncs.com",
+                    "oss-ap-northeast-2-internal.aliyuncs.com",
+                    &["首尔", "韩国"],
+                ),
+                Self::ApSouth => (
+                    "ap-south-1",
+                    "oss-ap-south-1.aliyuncs.com",
+                    "oss-ap-south-1-internal.aliyuncs.com",
+                    &["孟买"],
+                ),
+                Self::EuCentral => (
+                    "eu-central-1",
+                    "oss-eu-central-1.aliyuncs.com",
+                    "oss-eu-central-1-internal.aliyuncs.com",
+                    &["法兰克福", "德国"],
+                ),
+                Self::EuWest => (
+                    "eu-west-1",
+                    "oss-eu-west-1.aliyuncs.com",
+                    "oss-eu-west-1-internal.aliyuncs.com",
+                    &["伦敦", "英国"],
+                ),
+                Self::MeEast => (
+                    "me-east-1",
+                    "oss-me-east-1.aliyuncs.com",
+                    "oss-me-east-1-internal.aliyuncs.com",
+                    &["迪拜"],
+                ),
+                Self::Custom { .. } => unreachable!("custom region has no built-in endpoints"),
+            };
+        RegionInfo {
+            id,
+            endpoint,
+            internal_endpoint,
+            aliases,
+        }
+    }
+
+    /// Whether `s` (already lowercased) names this region: the region id with
+    /// or without the `oss-` prefix, a Chinese name, or an endpoint hostname,
+    /// optionally prefixed by a bucket.
+    fn matches(&self, s: &str) -> bool {
+        let info = self.info();
+        if s == info.id
+            || info.aliases.contains(&s)
+            || s.strip_prefix("oss-") == Some(info.id)
+            || s.replace('-', "") == format!("oss{}", info.id.replace('-', ""))
+        {
+            return true;
+        }
+        [info.endpoint, info.internal_endpoint]
+            .iter()
+            .any(|endpoint| {
+                s == *endpoint
+                    || s.strip_suffix(endpoint)
+                        .is_some_and(|bucket| bucket.ends_with('.'))
+            })
     }
 }
 
@@ -111,10 +421,75 @@ impl FromStr for Region {
     type Err = ParseRegionError;
 
     fn from_str(s: &str) -> Result<Self, ParseRegionError> {
-        let v: &str = &s.to_lowercase();
-        match v {
-            "北京" | "oss-cn-beijing" | "osscnbeijing" | "oss-cn-beijing.aliyuncs.com" | "oss-cn-beijing-internal.aliyuncs.com" => Ok(Region::BeiJing),
-            s => Err(ParseRegionError::new(s)),
+        let v = s.trim().to_lowercase();
+        let v = v
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_end_matches('/');
+        Region::ALL
+            .iter()
+            .find(|region| region.matches(v))
+            .cloned()
+            .ok_or_else(|| ParseRegionError::new(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_region_test() {
+        for region in Region::ALL.iter() {
+            assert_eq!(region.name().parse::<Region>().as_ref(), Ok(region));
+            assert_eq!(region.endpoint().parse::<Region>().as_ref(), Ok(region));
+            assert_eq!(
+                region.internal_endpoint().parse::<Region>().as_ref(),
+                Ok(region)
+            );
         }
+        assert_eq!("北京".parse(), Ok(Region::BeiJing));
+        assert_eq!("OSS-CN-BEIJING".parse(), Ok(Region::BeiJing));
+        assert_eq!("osscnbeijing".parse(), Ok(Region::BeiJing));
+        assert_eq!("新加坡".parse(), Ok(Region::ApSoutheast1));
+        assert_eq!(
+            "https://oss-cn-hangzhou-internal.aliyuncs.com/".parse(),
+            Ok(Region::HangZhou)
+        );
+        assert_eq!(
+            "my-bucket.oss-cn-shanghai.aliyuncs.com".parse(),
+            Ok(Region::ShangHai)
+        );
+        assert!("cn-atlantis".parse::<Region>().is_err());
+        assert!("xoss-cn-shanghai.aliyuncs.com".parse::<Region>().is_err());
+    }
+
+    #[test]
+    fn endpoint_type_test() {
+        let region = Region::HangZhou;
+        assert_eq!(
+            region.endpoint_of(EndpointType::Public),
+            "oss-cn-hangzhou.aliyuncs.com"
+        );
+        assert_eq!(
+            region.endpoint_of(EndpointType::Internal),
+            "oss-cn-hangzhou-internal.aliyuncs.com"
+        );
+        assert_eq!(
+            region.endpoint_of(EndpointType::Accelerate),
+            "oss-accelerate.aliyuncs.com"
+        );
+        assert_eq!(
+            region.endpoint_of(EndpointType::AccelerateOverseas),
+            "oss-accelerate-overseas.aliyuncs.com"
+        );
+
+        let custom = Region::custom("cn-private", "http://oss.example.com:8080/");
+        assert_eq!(custom.name(), "cn-private");
+        assert_eq!(custom.endpoint(), "oss.example.com:8080");
+        assert_eq!(
+            custom.endpoint_of(EndpointType::Internal),
+            "oss.example.com:8080"
+        );
     }
 }
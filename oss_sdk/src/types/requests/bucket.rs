@@ -0,0 +1,149 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer};
+
+use crate::{Acl, DataRedundancyType, Owner, StorageClass};
+
+/// Parameters of a `ListBuckets` request, all optional.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ListBucketsOptions {
+    pub prefix: Option<String>,
+    /// List the buckets after this one.
+    pub marker: Option<String>,
+    /// At most 1000, 100 by default.
+    pub max_keys: Option<u32>,
+}
+
+/// One page of the buckets of the account.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ListBucketsOutput {
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub marker: String,
+    #[serde(default)]
+    pub max_keys: u32,
+    #[serde(default)]
+    pub is_truncated: bool,
+    /// `marker` of the next page, if truncated.
+    #[serde(default)]
+    pub next_marker: String,
+    pub owner: Option<Owner>,
+    #[serde(default, deserialize_with = "bucket_list")]
+    pub buckets: Vec<BucketSummary>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BucketSummary {
+    pub name: String,
+    pub creation_date: DateTime<Utc>,
+    /// `oss-` and the region id, e.g. `oss-cn-hangzhou`.
+    pub location: String,
+    /// Region id, e.g. `cn-hangzhou`.
+    #[serde(default)]
+    pub region: String,
+    #[serde(default)]
+    pub extranet_endpoint: String,
+    #[serde(default)]
+    pub intranet_endpoint: String,
+    #[serde(default, deserialize_with = "crate::xml::parse_lenient")]
+    pub storage_class: Option<StorageClass>,
+}
+
+/// Settings of a new bucket, the OSS defaults where `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PutBucketOptions {
+    /// `Standard` by default.
+    pub storage_class: Option<StorageClass>,
+    /// `LRS` by default.
+    pub data_redundancy_type: Option<DataRedundancyType>,
+    /// `Private` by default.
+    pub acl: Option<Acl>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BucketInfo {
+    pub name: String,
+    pub creation_date: DateTime<Utc>,
+    /// `oss-` and the region id, e.g. `oss-cn-hangzhou`.
+    pub location: String,
+    #[serde(default)]
+    pub extranet_endpoint: String,
+    #[serde(default)]
+    pub intranet_endpoint: String,
+    #[serde(default, deserialize_with = "crate::xml::parse_lenient")]
+    pub storage_class: Option<StorageClass>,
+    #[serde(default, deserialize_with = "crate::xml::parse_lenient")]
+    pub data_redundancy_type: Option<DataRedundancyType>,
+    #[serde(rename = "AccessControlList", default, deserialize_with = "acl_grant")]
+    pub acl: Option<Acl>,
+    pub owner: Option<Owner>,
+    /// `Enabled` or `Suspended`, `None` if never enabled.
+    pub versioning: Option<String>,
+    pub transfer_acceleration: Option<String>,
+    pub cross_region_replication: Option<String>,
+    pub access_monitor: Option<String>,
+    pub resource_group_id: Option<String>,
+    #[serde(default)]
+    pub comment: String,
+}
+
+/// Storage used by a bucket, in bytes, and its number of objects. OSS
+/// updates it about once an hour.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct BucketStat {
+    pub storage: u64,
+    pub object_count: u64,
+    pub multipart_upload_count: u64,
+    pub live_channel_count: u64,
+    /// Unix time of the statistics, in seconds.
+    pub last_modified_time: i64,
+    pub standard_storage: u64,
+    pub standard_object_count: u64,
+    pub infrequent_access_storage: u64,
+    /// Billed storage, objects under 64 KB counting as 64 KB.
+    pub infrequent_access_real_storage: u64,
+    pub infrequent_access_object_count: u64,
+    pub archive_storage: u64,
+    pub archive_real_storage: u64,
+    pub archive_object_count: u64,
+    pub cold_archive_storage: u64,
+    pub cold_archive_real_storage: u64,
+    pub cold_archive_object_count: u64,
+    pub deep_cold_archive_storage: u64,
+    pub deep_cold_archive_real_storage: u64,
+    pub deep_cold_archive_object_count: u64,
+    pub multipart_part_count: u64,
+    pub delete_marker_count: u64,
+}
+
+fn bucket_list<'de, D>(d: D) -> Result<Vec<BucketSummary>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Buckets {
+        #[serde(rename = "Bucket", default)]
+        bucket: Vec<BucketSummary>,
+    }
+    Ok(Buckets::deserialize(d)?.bucket)
+}
+
+fn acl_grant<'de, D>(d: D) -> Result<Option<Acl>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct AccessControlList {
+        #[serde(
+            rename = "Grant",
+            default,
+            deserialize_with = "crate::xml::parse_lenient"
+        )]
+        grant: Option<Acl>,
+    }
+    Ok(AccessControlList::deserialize(d)?.grant)
+}
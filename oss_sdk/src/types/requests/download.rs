@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+
+use crate::ProgressCallback;
+
+/// How `download_file` splits and fetches the object.
+#[derive(Clone, Debug)]
+pub struct DownloadOptions {
+    /// Size of the ranges fetched, 8 MiB by default.
+    pub part_size: u64,
+    /// Ranges fetched at the same time, 4 by default.
+    pub concurrency: usize,
+    pub progress: Option<ProgressCallback>,
+    /// Makes downloads survive restarts: the fetched ranges are recorded in
+    /// a checkpoint file, and a later call for the same object and path
+    /// fetches only the missing ones, as long as the object is unchanged.
+    /// The partial download is kept on failure then.
+    pub resumable: bool,
+    /// Checkpoint of a resumable download, the destination path with
+    /// `.ossdownload` appended by default.
+    pub checkpoint_path: Option<PathBuf>,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            part_size: 8 * 1024 * 1024,
+            concurrency: 4,
+            progress: None,
+            resumable: false,
+            checkpoint_path: None,
+        }
+    }
+}
@@ -1,42 +1,76 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::{ByteStream, StorageClass};
+
+/// A `GetObject` request, only `key` is required.
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct GetObjectRequest {
-    /// <p>The bucket name containing the object. </p> <p>When using this API with an access point, you must direct requests to the access point hostname. The access point hostname takes the form <i>AccessPointName</i>-<i>AccountId</i>.s3-accesspoint.<i>Region</i>.amazonaws.com. When using this operation with an access point through the AWS SDKs, you provide the access point ARN in place of the bucket name. For more information about access point ARNs, see <a href="https://docs.aws.amazon.com/AmazonS3/latest/dev/using-access-points.html">Using Access Points</a> in the <i>Amazon Simple Storage Service Developer Guide</i>.</p> <p>When using this API with Amazon S3 on Outposts, you must direct requests to the S3 on Outposts hostname. The S3 on Outposts hostname takes the form <i>AccessPointName</i>-<i>AccountId</i>.<i>outpostID</i>.s3-outposts.<i>Region</i>.amazonaws.com. When using this operation using S3 on Outposts through the AWS SDKs, you provide the Outposts bucket ARN in place of the bucket name. For more information about S3 on Outposts ARNs, see <a href="https://docs.aws.amazon.com/AmazonS3/latest/dev/S3onOutposts.html">Using S3 on Outposts</a> in the <i>Amazon Simple Storage Service Developer Guide</i>.</p>
-    pub bucket: String,
-    /// <p>The account id of the expected bucket owner. If the bucket is owned by a different account, the request will fail with an HTTP <code>403 (Access Denied)</code> error.</p>
-    pub expected_bucket_owner: Option<String>,
-    /// <p>Return the object only if its entity tag (ETag) is the same as the one specified, otherwise return a 412 (precondition failed).</p>
+    pub key: String,
+    /// First and last (inclusive) byte to download, to the end of the object
+    /// when the last one is `None`.
+    pub range: Option<(u64, Option<u64>)>,
+    /// Fail with 416 on an invalid range instead of returning the whole object.
+    pub range_behavior_standard: bool,
+    /// Otherwise 412 Precondition Failed.
     pub if_match: Option<String>,
-    /// <p>Return the object only if it has been modified since the specified time, otherwise return a 304 (not modified).</p>
-    pub if_modified_since: Option<String>,
-    /// <p>Return the object only if its entity tag (ETag) is different from the one specified, otherwise return a 304 (not modified).</p>
+    /// Otherwise 304 Not Modified.
     pub if_none_match: Option<String>,
-    /// <p>Return the object only if it has not been modified since the specified time, otherwise return a 412 (precondition failed).</p>
-    pub if_unmodified_since: Option<String>,
-    /// <p>Key of the object to get.</p>
-    pub key: String,
-    /// <p>Part number of the object being read. This is a positive integer between 1 and 10,000. Effectively performs a 'ranged' GET request for the part specified. Useful for downloading just a part of an object.</p>
-    pub part_number: Option<i64>,
-    /// <p><p>Downloads the specified range bytes of an object. For more information about the HTTP Range header, see <a href="https://www.w3.org/Protocols/rfc2616/rfc2616-sec14.html#sec14.35">https://www.w3.org/Protocols/rfc2616/rfc2616-sec14.html#sec14.35</a>.</p> <note> <p>Amazon S3 doesn&#39;t support retrieving multiple ranges of data per <code>GET</code> request.</p> </note></p>
-    pub range: Option<String>,
-    pub request_payer: Option<String>,
-    /// <p>Sets the <code>Cache-Control</code> header of the response.</p>
+    /// Otherwise 304 Not Modified.
+    pub if_modified_since: Option<DateTime<Utc>>,
+    /// Otherwise 412 Precondition Failed.
+    pub if_unmodified_since: Option<DateTime<Utc>>,
+    /// Overrides the `Content-Type` of the response.
+    pub response_content_type: Option<String>,
+    pub response_content_language: Option<String>,
+    pub response_expires: Option<String>,
     pub response_cache_control: Option<String>,
-    /// <p>Sets the <code>Content-Disposition</code> header of the response</p>
     pub response_content_disposition: Option<String>,
-    /// <p>Sets the <code>Content-Encoding</code> header of the response.</p>
     pub response_content_encoding: Option<String>,
-    /// <p>Sets the <code>Content-Language</code> header of the response.</p>
-    pub response_content_language: Option<String>,
-    /// <p>Sets the <code>Content-Type</code> header of the response.</p>
-    pub response_content_type: Option<String>,
-    /// <p>Sets the <code>Expires</code> header of the response.</p>
-    pub response_expires: Option<String>,
-    /// <p>Specifies the algorithm to use to when encrypting the object (for example, AES256).</p>
-    pub sse_customer_algorithm: Option<String>,
-    /// <p>Specifies the customer-provided encryption key for Amazon S3 to use in encrypting data. This value is used to store the object and then it is discarded; Amazon S3 does not store the encryption key. The key must be appropriate for use with the algorithm specified in the <code>x-amz-server-side-encryption-customer-algorithm</code> header.</p>
-    pub sse_customer_key: Option<String>,
-    /// <p>Specifies the 128-bit MD5 digest of the encryption key according to RFC 1321. Amazon S3 uses this header for a message integrity check to ensure that the encryption key was transmitted without error.</p>
-    pub sse_customer_key_md5: Option<String>,
-    /// <p>VersionId used to reference a specific version of the object.</p>
+    /// `x-oss-process`, e.g. `image/resize,w_100` to get a thumbnail.
+    pub process: Option<String>,
+    /// A version other than the latest one of a versioned bucket.
+    pub version_id: Option<String>,
+    /// Bandwidth limit of the download, in bit/s (245760 to 838860800).
+    pub traffic_limit: Option<u64>,
+    /// `gzip` to let OSS compress text content; no ETag is returned then.
+    pub accept_encoding: Option<String>,
+}
+
+impl GetObjectRequest {
+    pub fn new<K: Into<String>>(key: K) -> Self {
+        Self {
+            key: key.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// `Content-Range` of a ranged download, both ends inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContentRange {
+    pub start: u64,
+    pub end: u64,
+    /// Size of the whole object
+    pub size: u64,
+}
+
+#[derive(Debug)]
+pub struct GetObjectOutput {
+    pub body: ByteStream,
+    /// Length of `body`, the whole object or the requested range.
+    pub content_length: u64,
+    pub content_range: Option<ContentRange>,
+    pub content_type: Option<String>,
+    /// As returned by OSS, quotes included. Empty if the content was compressed.
+    pub etag: String,
+    pub last_modified: Option<DateTime<Utc>>,
+    /// User metadata, keys without the `x-oss-meta-` prefix.
+    pub metadata: BTreeMap<String, String>,
+    pub storage_class: Option<StorageClass>,
     pub version_id: Option<String>,
-}
\ No newline at end of file
+    /// CRC-64/ECMA-182 of the whole object, even for ranged downloads.
+    pub crc64: Option<u64>,
+    pub request_id: String,
+}
@@ -0,0 +1,56 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::{ObjectType, ServerSideEncryption, StorageClass};
+
+/// Metadata of an object, without its content. `get_object_meta` only fills
+/// in the size, ETag, last-modified, CRC64 and version id.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObjectMeta {
+    pub size: u64,
+    /// As returned by OSS, quotes included.
+    pub etag: String,
+    pub last_modified: Option<DateTime<Utc>>,
+    pub content_type: Option<String>,
+    pub storage_class: Option<StorageClass>,
+    pub object_type: Option<ObjectType>,
+    /// CRC-64/ECMA-182 of the object, computed by OSS.
+    pub crc64: Option<u64>,
+    pub version_id: Option<String>,
+    /// Only set for Archive and colder objects a restore was requested for.
+    pub restore: Option<RestoreStatus>,
+    pub server_side_encryption: Option<ServerSideEncryption>,
+    /// User metadata, keys without the `x-oss-meta-` prefix.
+    pub metadata: BTreeMap<String, String>,
+    pub request_id: String,
+}
+
+/// Progress of the restore of an archived object, `x-oss-restore`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RestoreStatus {
+    InProgress,
+    /// Readable until `expiry`.
+    Restored {
+        expiry: Option<DateTime<Utc>>,
+    },
+}
+
+impl RestoreStatus {
+    /// `ongoing-request="false", expiry-date="Sun, 16 Apr 2017 08:12:33 GMT"`
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        let quoted = |key: &str| {
+            let start = s.find(key)? + key.len();
+            let end = s[start..].find('"')?;
+            Some(&s[start..start + end])
+        };
+        match quoted("ongoing-request=\"")? {
+            "true" => Some(RestoreStatus::InProgress),
+            _ => Some(RestoreStatus::Restored {
+                expiry: quoted("expiry-date=\"")
+                    .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+                    .map(|date| date.with_timezone(&Utc)),
+            }),
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::{CommonPrefix, ObjectType, StorageClass};
+
+/// Parameters of a `ListObjectsV2` request, all optional.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ListObjectsV2Options {
+    pub prefix: Option<String>,
+    /// Groups the keys sharing a prefix up to the delimiter, usually `/`,
+    /// into `common_prefixes`.
+    pub delimiter: Option<String>,
+    /// List the keys after this one.
+    pub start_after: Option<String>,
+    /// `next_continuation_token` of the previous page.
+    pub continuation_token: Option<String>,
+    /// At most 1000, 100 by default.
+    pub max_keys: Option<u32>,
+    /// Fill in the `owner` of each object.
+    pub fetch_owner: bool,
+}
+
+/// One page of a listing. Keys are always requested url-encoded, so that
+/// any key survives XML, and are decoded before being returned.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ListObjectsV2Output {
+    /// The bucket
+    pub name: String,
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub delimiter: String,
+    #[serde(default)]
+    pub start_after: String,
+    #[serde(default)]
+    pub max_keys: u32,
+    /// Number of objects and common prefixes in the page.
+    #[serde(default)]
+    pub key_count: u32,
+    #[serde(default)]
+    pub continuation_token: String,
+    /// `continuation_token` of the next page, if truncated.
+    #[serde(default)]
+    pub next_continuation_token: String,
+    pub is_truncated: bool,
+    #[serde(default)]
+    pub(crate) encoding_type: String,
+    #[serde(rename = "Contents", default)]
+    pub contents: Vec<ObjectSummary>,
+    #[serde(default)]
+    pub common_prefixes: Vec<CommonPrefix>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ObjectSummary {
+    pub key: String,
+    pub last_modified: DateTime<Utc>,
+    /// As returned by OSS, quotes included.
+    #[serde(rename = "ETag")]
+    pub etag: String,
+    #[serde(
+        rename = "Type",
+        default,
+        deserialize_with = "crate::xml::parse_lenient"
+    )]
+    pub object_type: Option<ObjectType>,
+    pub size: u64,
+    #[serde(default, deserialize_with = "crate::xml::parse_lenient")]
+    pub storage_class: Option<StorageClass>,
+    /// Only set when `fetch_owner` was.
+    pub owner: Option<Owner>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct Owner {
+    #[serde(rename = "ID")]
+    pub id: String,
+    #[serde(rename = "DisplayName", default)]
+    pub display_name: String,
+}
@@ -1 +1,17 @@
-mod get_object;
\ No newline at end of file
+mod bucket;
+mod download;
+mod get_object;
+mod head_object;
+mod list_objects;
+mod multipart;
+mod put_object;
+mod upload;
+
+pub use bucket::*;
+pub use download::*;
+pub use get_object::*;
+pub use head_object::*;
+pub use list_objects::*;
+pub use multipart::*;
+pub use put_object::*;
+pub use upload::*;
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct InitiateMultipartUploadOutput {
+    pub bucket: String,
+    pub key: String,
+    pub upload_id: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UploadPartOutput {
+    pub part_number: u32,
+    /// As returned by OSS, quotes included.
+    pub etag: String,
+    /// CRC-64/ECMA-182 of the part, computed by OSS.
+    pub crc64: Option<u64>,
+    pub request_id: String,
+}
+
+/// Object an `UploadPartCopy` copies from.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CopySource {
+    pub bucket: String,
+    pub key: String,
+    /// A version other than the latest one of a versioned bucket.
+    pub version_id: Option<String>,
+}
+
+impl CopySource {
+    pub fn new<B: Into<String>, K: Into<String>>(bucket: B, key: K) -> Self {
+        Self {
+            bucket: bucket.into(),
+            key: key.into(),
+            version_id: None,
+        }
+    }
+}
+
+/// A part to assemble, as listed in the `CompleteMultipartUpload` body.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CompletedPart {
+    pub part_number: u32,
+    #[serde(rename = "ETag")]
+    pub etag: String,
+}
+
+impl From<&UploadPartOutput> for CompletedPart {
+    fn from(part: &UploadPartOutput) -> Self {
+        Self {
+            part_number: part.part_number,
+            etag: part.etag.clone(),
+        }
+    }
+}
+
+impl From<&PartSummary> for CompletedPart {
+    fn from(part: &PartSummary) -> Self {
+        Self {
+            part_number: part.part_number,
+            etag: part.etag.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompleteMultipartUploadOptions {
+    /// Notifies an application server once the object is assembled.
+    pub callback: Option<Callback>,
+    /// Fail with 409 FileAlreadyExists instead of replacing an existing object.
+    pub forbid_overwrite: bool,
+}
+
+/// Upload callback: OSS POSTs `body` to `url` once the upload is complete and
+/// hands the server's response back as the result of the upload.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Callback {
+    pub url: String,
+    /// May reference system variables such as `${object}` or `${size}` and
+    /// the custom `vars`, e.g. `bucket=${bucket}&my_var=${x:my_var}`.
+    pub body: String,
+    /// `application/x-www-form-urlencoded` (the default) or `application/json`.
+    pub body_type: Option<String>,
+    /// `Host` header of the callback request, the host of `url` by default.
+    pub host: Option<String>,
+    /// Custom variables, their names must start with `x:`.
+    pub vars: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompleteMultipartUploadOutput {
+    pub bucket: String,
+    pub key: String,
+    /// As returned by OSS, quotes included.
+    pub etag: String,
+    /// Url of the object, empty when a callback was set.
+    pub location: String,
+    /// CRC-64/ECMA-182 of the whole object, computed by OSS.
+    pub crc64: Option<u64>,
+    pub version_id: Option<String>,
+    pub request_id: String,
+    /// Response of the callback server, when a callback was set.
+    pub callback_result: Option<Bytes>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct CompleteMultipartUploadResult {
+    #[serde(default)]
+    pub location: String,
+    pub bucket: String,
+    pub key: String,
+    #[serde(rename = "ETag")]
+    pub etag: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct CopyPartResult {
+    #[serde(rename = "ETag")]
+    pub etag: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ListPartsOptions {
+    /// At most 1000, the default.
+    pub max_parts: Option<u32>,
+    /// List the parts after this part number.
+    pub part_number_marker: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ListPartsOutput {
+    pub bucket: String,
+    pub key: String,
+    pub upload_id: String,
+    #[serde(default)]
+    pub part_number_marker: u32,
+    /// `part_number_marker` of the next page, if truncated.
+    #[serde(default)]
+    pub next_part_number_marker: u32,
+    #[serde(default)]
+    pub max_parts: u32,
+    pub is_truncated: bool,
+    #[serde(rename = "Part", default)]
+    pub parts: Vec<PartSummary>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PartSummary {
+    pub part_number: u32,
+    pub last_modified: DateTime<Utc>,
+    /// As returned by OSS, quotes included.
+    #[serde(rename = "ETag")]
+    pub etag: String,
+    pub size: u64,
+    #[serde(rename = "HashCrc64ecma", default)]
+    pub crc64: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ListMultipartUploadsOptions {
+    pub prefix: Option<String>,
+    /// Groups the keys sharing a prefix up to the delimiter, usually `/`,
+    /// into `common_prefixes`.
+    pub delimiter: Option<String>,
+    /// List the uploads after this key, and after `upload_id_marker` for it.
+    pub key_marker: Option<String>,
+    pub upload_id_marker: Option<String>,
+    /// At most 1000, the default.
+    pub max_uploads: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ListMultipartUploadsOutput {
+    pub bucket: String,
+    #[serde(default)]
+    pub key_marker: String,
+    #[serde(default)]
+    pub upload_id_marker: String,
+    /// `key_marker` of the next page, if truncated.
+    #[serde(default)]
+    pub next_key_marker: String,
+    /// `upload_id_marker` of the next page, if truncated.
+    #[serde(default)]
+    pub next_upload_id_marker: String,
+    #[serde(default)]
+    pub delimiter: String,
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub max_uploads: u32,
+    pub is_truncated: bool,
+    #[serde(rename = "Upload", default)]
+    pub uploads: Vec<MultipartUpload>,
+    #[serde(default)]
+    pub common_prefixes: Vec<CommonPrefix>,
+}
+
+/// An initiated upload, neither completed nor aborted yet.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MultipartUpload {
+    pub key: String,
+    pub upload_id: String,
+    pub initiated: DateTime<Utc>,
+}
+
+/// Keys grouped by the delimiter of a listing.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CommonPrefix {
+    pub prefix: String,
+}
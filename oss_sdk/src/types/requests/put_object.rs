@@ -0,0 +1,37 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::{Acl, ServerSideEncryption, StorageClass};
+
+/// Optional headers of a `PutObject` request.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PutObjectOptions {
+    /// Guessed by OSS from the key's extension when not set.
+    pub content_type: Option<String>,
+    pub cache_control: Option<String>,
+    pub content_disposition: Option<String>,
+    pub content_encoding: Option<String>,
+    pub expires: Option<DateTime<Utc>>,
+    /// User metadata, sent as `x-oss-meta-*` headers. Keys are given without
+    /// the prefix and are case-insensitive.
+    pub metadata: BTreeMap<String, String>,
+    pub acl: Option<Acl>,
+    pub storage_class: Option<StorageClass>,
+    /// Object tags, sent as the `x-oss-tagging` query string.
+    pub tagging: BTreeMap<String, String>,
+    pub server_side_encryption: Option<ServerSideEncryption>,
+    /// Fail with 409 FileAlreadyExists instead of replacing an existing object.
+    pub forbid_overwrite: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PutObjectOutput {
+    /// As returned by OSS, quotes included.
+    pub etag: String,
+    /// CRC-64/ECMA-182 of the object, computed by OSS.
+    pub crc64: Option<u64>,
+    /// Only set when versioning is enabled on the bucket.
+    pub version_id: Option<String>,
+    pub request_id: String,
+}
@@ -0,0 +1,98 @@
+use std::{fmt, path::PathBuf, sync::Arc};
+
+use crate::{PutObjectOptions, PutObjectOutput};
+
+/// Most parts a multipart upload may have.
+pub const MAX_PARTS: u64 = 10_000;
+/// Smallest size of any part but the last.
+pub const MIN_PART_SIZE: u64 = 100 * 1024;
+
+/// Bytes transferred so far by an upload or a download.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress {
+    pub transferred: u64,
+    /// `None` when the size of the source is unknown.
+    pub total: Option<u64>,
+}
+
+/// Called whenever a part has been transferred, possibly from several tasks
+/// at once.
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(Progress) + Send + Sync>);
+
+impl ProgressCallback {
+    pub fn new<F: Fn(Progress) + Send + Sync + 'static>(f: F) -> Self {
+        ProgressCallback(Arc::new(f))
+    }
+    pub(crate) fn call(&self, progress: Progress) {
+        (self.0)(progress)
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+/// How `upload_file` and `upload_stream` split and send the source.
+#[derive(Clone, Debug)]
+pub struct UploadOptions {
+    /// Headers of the uploaded object.
+    pub object: PutObjectOptions,
+    /// Sources of this size or larger are uploaded in parts, 100 MiB by default.
+    pub multipart_threshold: u64,
+    /// Preferred part size, 8 MiB by default. It is raised when a source of
+    /// known size would need more than `MAX_PARTS` parts.
+    pub part_size: u64,
+    /// Parts uploaded at the same time, 4 by default.
+    pub concurrency: usize,
+    pub progress: Option<ProgressCallback>,
+    /// Makes multipart uploads by `upload_file` survive restarts: the
+    /// completed parts are recorded in a checkpoint file, and a later call
+    /// for the same file and key uploads only the missing ones. The upload is
+    /// not aborted on failure then.
+    pub resumable: bool,
+    /// Checkpoint of a resumable upload, the source path with a hash of the
+    /// bucket and key and `.ossupload` appended by default. Resuming from the
+    /// checkpoint of another object fails.
+    pub checkpoint_path: Option<PathBuf>,
+}
+
+impl Default for UploadOptions {
+    fn default() -> Self {
+        Self {
+            object: PutObjectOptions::default(),
+            multipart_threshold: 100 * 1024 * 1024,
+            part_size: 8 * 1024 * 1024,
+            concurrency: 4,
+            progress: None,
+            resumable: false,
+            checkpoint_path: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UploadOutput {
+    /// As returned by OSS, quotes included.
+    pub etag: String,
+    /// CRC-64/ECMA-182 of the object, computed by OSS.
+    pub crc64: Option<u64>,
+    pub version_id: Option<String>,
+    pub request_id: String,
+    /// Set when the source was uploaded in parts.
+    pub upload_id: Option<String>,
+}
+
+impl From<PutObjectOutput> for UploadOutput {
+    fn from(output: PutObjectOutput) -> Self {
+        Self {
+            etag: output.etag,
+            crc64: output.crc64,
+            version_id: output.version_id,
+            request_id: output.request_id,
+            upload_id: None,
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use http::StatusCode;
+
+/// When and how often `OSSClient` sends a failed request again. Every
+/// attempt is signed anew, and requests with a streaming body are never
+/// retried since the stream cannot be replayed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts in total, the first one included; 1 disables retries.
+    pub max_attempts: u32,
+    /// Upper bound of the wait before the first retry, doubled on each
+    /// further one. The actual wait is picked at random below it.
+    pub base_delay: Duration,
+    /// Upper bound of any wait.
+    pub max_delay: Duration,
+    /// Responses retried besides network errors.
+    pub retryable_statuses: Vec<StatusCode>,
+    /// OSS error codes retried whatever their status.
+    pub retryable_codes: Vec<String>,
+    /// Time allowed for all the attempts of a request together.
+    pub deadline: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    /// 3 attempts, waits of up to 200 ms, 400 ms, … capped at 10 s, on
+    /// network errors, 429, 500, 502, 503, 504 and `RequestTimeTooSkewed`.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(10),
+            retryable_statuses: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            retryable_codes: vec!["RequestTimeTooSkewed".to_owned()],
+            deadline: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Sends every request once.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+    /// Wait before retry number `retry`, counted from 1.
+    pub(crate) fn delay(&self, retry: u32) -> Duration {
+        let ceiling = self
+            .base_delay
+            .checked_mul(1 << (retry - 1).min(31))
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay));
+        // Full jitter, so clients failing together do not retry together
+        let random = RandomState::new().build_hasher().finish();
+        ceiling.mul_f64(random as f64 / u64::MAX as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_test() {
+        let policy = RetryPolicy::default();
+        for retry in 1..40 {
+            let ceiling = (Duration::from_millis(200) * 2u32.pow((retry - 1).min(20)))
+                .min(Duration::from_secs(10));
+            assert!(policy.delay(retry) <= ceiling);
+        }
+        let delays: Vec<Duration> = (0..10).map(|_| policy.delay(3)).collect();
+        assert!(delays.iter().any(|delay| *delay != delays[0]));
+    }
+}
@@ -0,0 +1,9 @@
+/// Scheme used to sign requests.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SignatureVersion {
+    /// Legacy `OSS AccessKeyId:Signature` HMAC-SHA1 signature
+    #[default]
+    V1,
+    /// `OSS4-HMAC-SHA256` signature, scoped to a date and a region
+    V4,
+}
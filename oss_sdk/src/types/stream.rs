@@ -6,7 +6,11 @@ use std::task::{Context, Poll};
 use bytes::{BufMut, Bytes, BytesMut};
 use futures::{future, stream, Stream, StreamExt};
 use pin_project_lite::pin_project;
-use tokio::io::{AsyncRead, ReadBuf};
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, ReadBuf};
+
+/// Size of the chunks read from files and readers.
+const CHUNK_SIZE: usize = 64 * 1024;
 
 pin_project! {
     /// Stream of bytes.
@@ -45,6 +49,33 @@ impl ByteStream {
         self.size_hint
     }
 
+    /// Creates a new `ByteStream` reading `reader` by chunks of 64 KiB.
+    /// Without a `size_hint` the stream is uploaded with chunked transfer encoding.
+    pub fn from_async_read<R>(reader: R, size_hint: Option<usize>) -> ByteStream
+    where
+        R: AsyncRead + Send + 'static,
+    {
+        let stream = stream::try_unfold(Box::pin(reader), |mut reader| async move {
+            let mut buf = BytesMut::with_capacity(CHUNK_SIZE);
+            match reader.read_buf(&mut buf).await? {
+                0 => Ok(None),
+                _ => Ok(Some((buf.freeze(), reader))),
+            }
+        });
+        ByteStream {
+            size_hint,
+            inner: Box::pin(stream),
+        }
+    }
+
+    /// Creates a new `ByteStream` reading `file` from its current position to
+    /// its end, which gives the size hint.
+    pub async fn from_file(mut file: File) -> io::Result<ByteStream> {
+        let position = file.stream_position().await?;
+        let len = file.metadata().await?.len().saturating_sub(position);
+        Ok(Self::from_async_read(file, Some(len as usize)))
+    }
+
     /// Return an implementation of `AsyncRead` that uses async i/o to consume the stream.
     pub fn into_async_read(self) -> impl AsyncRead + Send {
         ImplAsyncRead::new(self.inner)
@@ -198,6 +229,28 @@ fn test_blocking_read() {
     assert_eq!(async_read.read(&mut buf).unwrap(), 0);
 }
 
+#[tokio::test]
+async fn test_from_async_read() {
+    use std::io::SeekFrom;
+
+    let data: Vec<u8> = (0..CHUNK_SIZE * 2 + 10).map(|i| i as u8).collect();
+    let stream = ByteStream::from_async_read(io::Cursor::new(data.clone()), None);
+    assert_eq!(stream.size_hint(), None);
+    let chunks: Vec<Bytes> = stream.map(Result::unwrap).collect().await;
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(chunks.concat(), data);
+
+    let path = crate::test_util::temp_path("stream_from_file");
+    std::fs::write(&path, &data).unwrap();
+    let mut file = File::open(&path).await.unwrap();
+    file.seek(SeekFrom::Start(10)).await.unwrap();
+    let stream = ByteStream::from_file(file).await.unwrap();
+    assert_eq!(stream.size_hint(), Some(CHUNK_SIZE * 2));
+    let chunks: Vec<Bytes> = stream.map(Result::unwrap).collect().await;
+    assert_eq!(chunks.concat(), &data[10..]);
+    std::fs::remove_file(path).unwrap();
+}
+
 #[tokio::test]
 async fn test_new_with_size_read() {
     use bytes::Bytes;
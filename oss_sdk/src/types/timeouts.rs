@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+/// How long `OSSClient` waits on the network. `None` waits forever.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timeouts {
+    /// To establish a connection. Fixed when the HTTP client is built, so it
+    /// cannot be overridden per request.
+    pub connect: Option<Duration>,
+    /// For a whole request, from sending it to the end of the response body.
+    /// Unbounded by default, as large objects take as long as they take.
+    pub request: Option<Duration>,
+    /// Between two chunks of a response body, so a stalled download fails
+    /// however long it was allowed to run.
+    pub read: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    /// 10 s to connect and 60 s without receiving data.
+    fn default() -> Self {
+        Self {
+            connect: Some(Duration::from_secs(10)),
+            request: None,
+            read: Some(Duration::from_secs(60)),
+        }
+    }
+}
@@ -0,0 +1,739 @@
+use std::{
+    collections::HashMap,
+    io::{self, SeekFrom},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::{future, stream, Stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncReadExt, AsyncSeekExt},
+    sync::Mutex,
+};
+
+use crate::{
+    checkpoint, download::CRC64, http_client::SignAndDispatch, CompleteMultipartUploadOptions,
+    CompletedPart, Error, OSSClient, PartSummary, Progress, Result, UploadOptions, UploadOutput,
+    UploadPartOutput, MAX_PARTS, MIN_PART_SIZE,
+};
+
+/// Progress of a resumable upload, saved as JSON.
+#[derive(Debug, Serialize, Deserialize)]
+struct UploadCheckpoint {
+    bucket: String,
+    key: String,
+    upload_id: String,
+    part_size: u64,
+    /// Size and modification time of the source when the upload started.
+    file_size: u64,
+    file_mtime: DateTime<Utc>,
+    parts: Vec<CheckpointPart>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CheckpointPart {
+    part_number: u32,
+    etag: String,
+    crc64: Option<u64>,
+}
+
+/// Records the parts of a resumable upload as they complete.
+struct Checkpointer<'a> {
+    path: &'a Path,
+    checkpoint: Mutex<UploadCheckpoint>,
+}
+
+impl Checkpointer<'_> {
+    async fn record(&self, part: &UploadPartOutput) -> Result<()> {
+        let mut checkpoint = self.checkpoint.lock().await;
+        checkpoint.parts.push(CheckpointPart {
+            part_number: part.part_number,
+            etag: part.etag.clone(),
+            crc64: part.crc64,
+        });
+        Ok(checkpoint::save(self.path, &*checkpoint).await?)
+    }
+}
+
+impl<C: SignAndDispatch + Send + Sync> OSSClient<C> {
+    /// Uploads the file at `path` as `key`, in parts when it is at least
+    /// `options.multipart_threshold` large. See `UploadOptions::resumable`
+    /// to resume an interrupted upload.
+    pub async fn upload_file<K, P>(
+        &self,
+        key: K,
+        path: P,
+        options: UploadOptions,
+    ) -> Result<UploadOutput>
+    where
+        K: Into<String>,
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file = File::open(path).await?;
+        let metadata = file.metadata().await?;
+        let size = metadata.len();
+        if !options.resumable || size < options.multipart_threshold {
+            return self.upload_stream(key, file, Some(size), options).await;
+        }
+
+        let key = key.into();
+        let checkpoint_path = match options.checkpoint_path {
+            Some(ref checkpoint_path) => checkpoint_path.clone(),
+            None => checkpoint_path(path, self.get_bucket(), &key),
+        };
+        let file_mtime = DateTime::<Utc>::from(metadata.modified()?);
+        let checkpoint = match self
+            .resume_upload(&checkpoint_path, &key, size, file_mtime)
+            .await?
+        {
+            Some(checkpoint) => checkpoint,
+            None => UploadCheckpoint {
+                bucket: self.get_bucket().to_owned(),
+                key: key.clone(),
+                upload_id: self
+                    .initiate_multipart_upload(key.as_str(), options.object.clone())
+                    .await?
+                    .upload_id,
+                part_size: part_size(Some(size), options.part_size),
+                file_size: size,
+                file_mtime,
+                parts: Vec::new(),
+            },
+        };
+        checkpoint::save(&checkpoint_path, &checkpoint).await?;
+
+        let part_size = checkpoint.part_size;
+        let upload_id = checkpoint.upload_id.clone();
+        let part_count = size.div_ceil(part_size).max(1) as u32;
+        let done: Vec<u32> = checkpoint.parts.iter().map(|p| p.part_number).collect();
+        let pending: Vec<u32> = (1..=part_count).filter(|n| !done.contains(n)).collect();
+        let transferred = done
+            .iter()
+            .map(|n| part_size.min(size - (*n as u64 - 1) * part_size))
+            .sum();
+        let parts = stream::try_unfold(
+            (file, pending.into_iter()),
+            move |(mut file, mut pending)| async move {
+                let number = match pending.next() {
+                    Some(number) => number,
+                    None => return Ok(None),
+                };
+                file.seek(SeekFrom::Start((number as u64 - 1) * part_size))
+                    .await?;
+                let body = read_part(&mut file, part_size).await?;
+                Ok(Some(((number, body), (file, pending))))
+            },
+        );
+        let checkpointer = Checkpointer {
+            path: &checkpoint_path,
+            checkpoint: Mutex::new(checkpoint),
+        };
+        self.upload_parts(
+            &key,
+            &upload_id,
+            parts,
+            Some(size),
+            transferred,
+            &options,
+            Some(&checkpointer),
+        )
+        .await?;
+
+        let mut parts: Vec<CompletedPart> = checkpointer
+            .checkpoint
+            .into_inner()
+            .parts
+            .into_iter()
+            .map(|part| CompletedPart {
+                part_number: part.part_number,
+                etag: part.etag,
+            })
+            .collect();
+        parts.sort_by_key(|part| part.part_number);
+        let complete = CompleteMultipartUploadOptions {
+            forbid_overwrite: options.object.forbid_overwrite,
+            ..Default::default()
+        };
+        let output = self
+            .complete_multipart_upload(key, &upload_id, &parts, complete)
+            .await?;
+        checkpoint::remove(&checkpoint_path).await?;
+        Ok(UploadOutput {
+            etag: output.etag,
+            crc64: output.crc64,
+            version_id: output.version_id,
+            request_id: output.request_id,
+            upload_id: Some(upload_id),
+        })
+    }
+
+    /// The checkpoint at `path`, if it is of an upload of this very file that
+    /// still exists, with only the parts OSS lists as uploaded. A checkpoint of
+    /// another object is an error: its upload may well still be running.
+    async fn resume_upload(
+        &self,
+        path: &Path,
+        key: &str,
+        file_size: u64,
+        file_mtime: DateTime<Utc>,
+    ) -> Result<Option<UploadCheckpoint>> {
+        let mut checkpoint: UploadCheckpoint = match checkpoint::load(path).await {
+            Some(checkpoint) => checkpoint,
+            None => return Ok(None),
+        };
+        if checkpoint.bucket != self.get_bucket() || checkpoint.key != key {
+            return Err(Error::config(format!(
+                "{} is the checkpoint of {}/{}, not of {}/{}",
+                path.display(),
+                checkpoint.bucket,
+                checkpoint.key,
+                self.get_bucket(),
+                key
+            )));
+        }
+        if checkpoint.file_size != file_size || checkpoint.file_mtime != file_mtime {
+            log::info!("{} changed, restarting its upload", key);
+            self.abort_stale_upload(&checkpoint).await;
+            return Ok(None);
+        }
+        let listed: HashMap<u32, PartSummary> = match self
+            .list_parts_stream(key, &checkpoint.upload_id)
+            .map_ok(|part| (part.part_number, part))
+            .try_collect()
+            .await
+        {
+            Ok(listed) => listed,
+            Err(e) if e.is_not_found() => {
+                log::info!("upload {} is gone, restarting it", checkpoint.upload_id);
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+        checkpoint.parts.retain(|part| {
+            listed.get(&part.part_number).is_some_and(|listed| {
+                listed.etag == part.etag && (part.crc64.is_none() || listed.crc64 == part.crc64)
+            })
+        });
+        Ok(Some(checkpoint))
+    }
+
+    /// Aborts the upload of `checkpoint`, which is about to be overwritten,
+    /// so that its parts are not left behind with nothing pointing at them.
+    async fn abort_stale_upload(&self, checkpoint: &UploadCheckpoint) {
+        let mut rqst = self.del_request(checkpoint.key.as_str());
+        rqst.add_params("uploadId", checkpoint.upload_id.as_str());
+        if let Err(e) = self.send(rqst).await {
+            log::warn!(
+                "failed to abort multipart upload {}: {}",
+                checkpoint.upload_id,
+                e
+            );
+        }
+    }
+
+    /// Uploads `reader` as `key`, in parts when its `size` is at least
+    /// `options.multipart_threshold`. When the size is unknown, a source
+    /// shorter than one part is sent in a single request and a longer one in
+    /// parts of `options.part_size`.
+    ///
+    /// At most `options.concurrency` parts are buffered at once. The upload
+    /// is aborted when a part fails for good, so no orphaned parts are left
+    /// behind.
+    pub async fn upload_stream<K, R>(
+        &self,
+        key: K,
+        reader: R,
+        size: Option<u64>,
+        options: UploadOptions,
+    ) -> Result<UploadOutput>
+    where
+        K: Into<String>,
+        R: AsyncRead + Send + 'static,
+    {
+        let key = key.into();
+        let mut reader = Box::pin(reader);
+        if let Some(size) = size.filter(|size| *size < options.multipart_threshold) {
+            let output = self
+                .put_object_from_reader(key, reader, Some(size), options.object.clone())
+                .await?;
+            report(&options, size, Some(size));
+            return Ok(output.into());
+        }
+
+        let part_size = part_size(size, options.part_size);
+        let first = read_part(&mut reader, part_size).await?;
+        let len = first.len() as u64;
+        if size.is_none() && len < part_size && len < options.multipart_threshold {
+            let output = self.put_object(key, first, options.object.clone()).await?;
+            report(&options, len, Some(len));
+            return Ok(output.into());
+        }
+
+        let upload_id = self
+            .initiate_multipart_upload(key.as_str(), options.object.clone())
+            .await?
+            .upload_id;
+        let rest = stream::try_unfold((reader, 2), move |(mut reader, number)| async move {
+            let body = read_part(&mut reader, part_size).await?;
+            if body.is_empty() {
+                return Ok(None);
+            }
+            if number > MAX_PARTS {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("source exceeds {} parts of {} bytes", MAX_PARTS, part_size),
+                ));
+            }
+            Ok(Some(((number as u32, body), (reader, number + 1))))
+        });
+        let parts = stream::once(future::ready(Ok((1, first)))).chain(rest);
+        let result = async {
+            let parts = self
+                .upload_parts(&key, &upload_id, parts, size, 0, &options, None)
+                .await?;
+            let complete = CompleteMultipartUploadOptions {
+                forbid_overwrite: options.object.forbid_overwrite,
+                ..Default::default()
+            };
+            self.complete_multipart_upload(key.as_str(), &upload_id, &parts, complete)
+                .await
+        }
+        .await;
+        match result {
+            Ok(output) => Ok(UploadOutput {
+                etag: output.etag,
+                crc64: output.crc64,
+                version_id: output.version_id,
+                request_id: output.request_id,
+                upload_id: Some(upload_id),
+            }),
+            Err(e) => {
+                if let Err(abort) = self.abort_multipart_upload(key, &upload_id).await {
+                    log::warn!("failed to abort multipart upload {}: {}", upload_id, abort);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Uploads `parts` `options.concurrency` at a time, `transferred` bytes
+    /// having been uploaded before.
+    #[allow(clippy::too_many_arguments)]
+    async fn upload_parts<S>(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: S,
+        total: Option<u64>,
+        transferred: u64,
+        options: &UploadOptions,
+        checkpointer: Option<&Checkpointer<'_>>,
+    ) -> Result<Vec<CompletedPart>>
+    where
+        S: Stream<Item = io::Result<(u32, Bytes)>> + Send,
+    {
+        let transferred = &AtomicU64::new(transferred);
+        let mut parts: Vec<CompletedPart> = parts
+            .map_err(Error::from)
+            .map_ok(|(number, body)| async move {
+                let len = body.len() as u64;
+                let part = self.upload_part(key, upload_id, number, body).await?;
+                if let Some(checkpointer) = checkpointer {
+                    checkpointer.record(&part).await?;
+                }
+                let transferred = transferred.fetch_add(len, Ordering::Relaxed) + len;
+                report(options, transferred, total);
+                Ok(CompletedPart::from(&part))
+            })
+            .try_buffer_unordered(options.concurrency.max(1))
+            .try_collect()
+            .await?;
+        parts.sort_by_key(|part| part.part_number);
+        Ok(parts)
+    }
+}
+
+/// `preferred`, raised to fit a source of `size` in `MAX_PARTS` parts.
+fn part_size(size: Option<u64>, preferred: u64) -> u64 {
+    let smallest = size.map_or(0, |size| size.div_ceil(MAX_PARTS));
+    preferred.max(smallest).max(MIN_PART_SIZE)
+}
+
+/// Default checkpoint of uploading `path` as `bucket/key`, e.g.
+/// `backup.tar.3b8f21c7a94d0e56.ossupload`, so that uploads of one file to
+/// several objects don't share it.
+fn checkpoint_path(path: &Path, bucket: &str, key: &str) -> PathBuf {
+    let object = CRC64.checksum(format!("{}/{}", bucket, key).as_bytes());
+    checkpoint::next_to(path, &format!(".{:016x}.ossupload", object))
+}
+
+/// Up to `size` bytes, fewer only at the end of `reader`.
+async fn read_part<R: AsyncRead + Unpin>(reader: &mut R, size: u64) -> io::Result<Bytes> {
+    let mut buf = Vec::with_capacity(size as usize);
+    reader.take(size).read_to_end(&mut buf).await?;
+    Ok(buf.into())
+}
+
+fn report(options: &UploadOptions, transferred: u64, total: Option<u64>) {
+    if let Some(ref progress) = options.progress {
+        progress.call(Progress { transferred, total });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, AtomicUsize},
+        Arc, Mutex,
+    };
+
+    use super::*;
+    use crate::{
+        test_util::{mock_client, temp_path, MockRequest, MockResponse, MockServer},
+        ProgressCallback,
+    };
+
+    const SIZE: usize = 2 * MIN_PART_SIZE as usize + 1000;
+
+    /// Fails the first attempt at part 2 with a 500, and every attempt at
+    /// part `fatal_part` with a 403.
+    fn upload_server(fatal_part: &'static str) -> impl Fn(&MockRequest) -> MockResponse {
+        let failed = AtomicBool::new(false);
+        move |rqst| match (rqst.method.as_str(), rqst.param("partNumber").as_deref()) {
+            ("PUT", None) => MockResponse::ok("").header("etag", "\"single\""),
+            ("PUT", Some("2")) if !failed.swap(true, Ordering::SeqCst) => MockResponse::status(500),
+            ("PUT", Some(part)) if part == fatal_part => MockResponse::status(403),
+            ("PUT", Some(part)) => MockResponse::ok("").header("etag", format!("\"{}\"", part)),
+            ("POST", _) if rqst.param("uploads").is_some() => MockResponse::ok(
+                "<InitiateMultipartUploadResult><Bucket>bucket</Bucket><Key>big.bin</Key>\
+                 <UploadId>id</UploadId></InitiateMultipartUploadResult>",
+            ),
+            ("POST", _) => MockResponse::ok(
+                "<CompleteMultipartUploadResult><Bucket>bucket</Bucket><Key>big.bin</Key>\
+                 <ETag>\"multipart\"</ETag></CompleteMultipartUploadResult>",
+            )
+            .header("x-oss-hash-crc64ecma", "1"),
+            ("DELETE", _) => MockResponse::status(204),
+            _ => MockResponse::status(400),
+        }
+    }
+
+    fn multipart_options() -> UploadOptions {
+        UploadOptions {
+            multipart_threshold: 0,
+            part_size: MIN_PART_SIZE,
+            concurrency: 2,
+            ..Default::default()
+        }
+    }
+
+    /// Lists parts 1 and 2 of upload `id`, any other upload being gone, and
+    /// fails part 3 with a 403 while `fatal` is set.
+    fn resumable_server(fatal: Arc<AtomicBool>) -> impl Fn(&MockRequest) -> MockResponse {
+        let inner = upload_server("0");
+        move |rqst| {
+            match (rqst.method.as_str(), rqst.param("uploadId").as_deref()) {
+            ("GET", Some("id")) => MockResponse::ok(
+                "<ListPartsResult><Bucket>bucket</Bucket><Key>big.bin</Key>\
+                 <UploadId>id</UploadId><IsTruncated>false</IsTruncated>\
+                 <Part><PartNumber>1</PartNumber><LastModified>2012-02-23T07:01:34.000Z</LastModified>\
+                 <ETag>\"1\"</ETag><Size>102400</Size></Part>\
+                 <Part><PartNumber>2</PartNumber><LastModified>2012-02-23T07:01:34.000Z</LastModified>\
+                 <ETag>\"2\"</ETag><Size>102400</Size></Part></ListPartsResult>",
+            ),
+            ("GET", _) => MockResponse::status(404),
+            ("PUT", _)
+                if rqst.param("partNumber").as_deref() == Some("3")
+                    && fatal.load(Ordering::SeqCst) =>
+            {
+                MockResponse::status(403)
+            }
+            _ => inner(rqst),
+        }
+        }
+    }
+
+    #[test]
+    fn part_size_test() {
+        assert_eq!(part_size(None, 8 << 20), 8 << 20);
+        assert_eq!(part_size(Some(1 << 20), 1), MIN_PART_SIZE);
+        assert_eq!(part_size(Some(100_000 << 20), 8 << 20), 10 << 20);
+        assert_eq!(
+            part_size(Some((100_000 << 20) + 1), 8 << 20),
+            (10 << 20) + 1
+        );
+    }
+
+    #[tokio::test]
+    async fn upload_file_test() {
+        let server = MockServer::start(upload_server("0")).await;
+        let oss_instance = mock_client(&server);
+        let path = temp_path("upload");
+        let data: Vec<u8> = (0..SIZE).map(|i| i as u8).collect();
+        tokio::fs::write(&path, &data).await.unwrap();
+
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let recorded = progress.clone();
+        let options = UploadOptions {
+            progress: Some(ProgressCallback::new(move |p: Progress| {
+                recorded.lock().unwrap().push(p)
+            })),
+            ..multipart_options()
+        };
+        let output = oss_instance
+            .upload_file("big.bin", &path, options)
+            .await
+            .unwrap();
+        tokio::fs::remove_file(&path).await.unwrap();
+        assert_eq!(output.etag, "\"multipart\"");
+        assert_eq!(output.crc64, Some(1));
+        assert_eq!(output.upload_id.as_deref(), Some("id"));
+
+        let progress = progress.lock().unwrap();
+        assert_eq!(progress.len(), 3);
+        assert_eq!(
+            progress.last(),
+            Some(&Progress {
+                transferred: SIZE as u64,
+                total: Some(SIZE as u64)
+            })
+        );
+
+        let requests = server.requests();
+        let mut parts: Vec<_> = requests
+            .iter()
+            .filter(|rqst| rqst.method == "PUT")
+            .map(|rqst| (rqst.param("partNumber").unwrap(), rqst.body.clone()))
+            .collect();
+        // Part 2 was sent twice
+        assert_eq!(parts.len(), 4);
+        parts.sort();
+        parts.dedup();
+        let body: Vec<u8> = parts.into_iter().flat_map(|(_, body)| body).collect();
+        assert_eq!(body, data);
+        let complete = requests.last().unwrap();
+        assert_eq!(complete.method, "POST");
+        assert_eq!(
+            String::from_utf8(complete.body.clone()).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><CompleteMultipartUpload>\
+             <Part><PartNumber>1</PartNumber><ETag>&quot;1&quot;</ETag></Part>\
+             <Part><PartNumber>2</PartNumber><ETag>&quot;2&quot;</ETag></Part>\
+             <Part><PartNumber>3</PartNumber><ETag>&quot;3&quot;</ETag></Part>\
+             </CompleteMultipartUpload>"
+        );
+    }
+
+    #[tokio::test]
+    async fn upload_stream_abort_test() {
+        let server = MockServer::start(upload_server("3")).await;
+        let oss_instance = mock_client(&server);
+        let data = vec![7u8; SIZE];
+
+        let err = oss_instance
+            .upload_stream("big.bin", io::Cursor::new(data), None, multipart_options())
+            .await
+            .unwrap_err();
+        assert_eq!(err.status().map(|s| s.as_u16()), Some(403));
+        let requests = server.requests();
+        // The 403 is not retried
+        assert_eq!(
+            requests
+                .iter()
+                .filter(|rqst| rqst.param("partNumber").as_deref() == Some("3"))
+                .count(),
+            1
+        );
+        let abort = requests.last().unwrap();
+        assert_eq!(abort.method, "DELETE");
+        assert_eq!(abort.param("uploadId").as_deref(), Some("id"));
+    }
+
+    #[tokio::test]
+    async fn upload_stream_single_put_test() {
+        let server = MockServer::start(upload_server("0")).await;
+        let oss_instance = mock_client(&server);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = calls.clone();
+        let options = UploadOptions {
+            progress: Some(ProgressCallback::new(move |p: Progress| {
+                assert_eq!(p.transferred, 5);
+                counted.fetch_add(1, Ordering::SeqCst);
+            })),
+            ..Default::default()
+        };
+
+        // Shorter than one part, unknown size
+        let output = oss_instance
+            .upload_stream("small.txt", &b"hello"[..], None, options.clone())
+            .await
+            .unwrap();
+        assert_eq!(output.etag, "\"single\"");
+        assert_eq!(output.upload_id, None);
+        // Known size under the threshold
+        oss_instance
+            .upload_stream("small.txt", &b"hello"[..], Some(5), options)
+            .await
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|rqst| rqst.body == b"hello"));
+    }
+
+    #[tokio::test]
+    async fn resumable_upload_test() {
+        let fatal = Arc::new(AtomicBool::new(true));
+        let server = MockServer::start(resumable_server(fatal.clone())).await;
+        let oss_instance = mock_client(&server);
+        let path = temp_path("resumable_upload");
+        let checkpoint_path = super::checkpoint_path(&path, "bucket", "big.bin");
+        assert_ne!(
+            checkpoint_path,
+            super::checkpoint_path(&path, "bucket", "copy.bin")
+        );
+        tokio::fs::write(&path, vec![1u8; SIZE]).await.unwrap();
+        // One part at a time, so parts 1 and 2 are done when part 3 fails
+        let options = UploadOptions {
+            resumable: true,
+            concurrency: 1,
+            ..multipart_options()
+        };
+
+        let err = oss_instance
+            .upload_file("big.bin", &path, options.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(err.status().map(|s| s.as_u16()), Some(403));
+        let interrupted = server.requests().len();
+        assert!(server.requests().iter().all(|rqst| rqst.method != "DELETE"));
+        let saved: UploadCheckpoint = checkpoint::load(&checkpoint_path).await.unwrap();
+        assert_eq!(saved.upload_id, "id");
+        assert_eq!(saved.file_size, SIZE as u64);
+        let mut saved: Vec<u32> = saved.parts.iter().map(|p| p.part_number).collect();
+        saved.sort_unstable();
+        assert_eq!(saved, vec![1, 2]);
+
+        fatal.store(false, Ordering::SeqCst);
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let recorded = progress.clone();
+        let options = UploadOptions {
+            progress: Some(ProgressCallback::new(move |p: Progress| {
+                recorded.lock().unwrap().push(p.transferred)
+            })),
+            ..options
+        };
+        let output = oss_instance
+            .upload_file("big.bin", &path, options)
+            .await
+            .unwrap();
+        assert_eq!(output.upload_id.as_deref(), Some("id"));
+        assert_eq!(*progress.lock().unwrap(), vec![SIZE as u64]);
+        assert!(checkpoint::load::<UploadCheckpoint>(&checkpoint_path)
+            .await
+            .is_none());
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        let requests = &server.requests()[interrupted..];
+        let methods: Vec<&str> = requests.iter().map(|r| r.method.as_str()).collect();
+        assert_eq!(methods, vec!["GET", "PUT", "POST"]);
+        assert_eq!(requests[1].param("partNumber").as_deref(), Some("3"));
+        assert_eq!(requests[1].body.len(), 1000);
+        assert!(String::from_utf8(requests[2].body.clone())
+            .unwrap()
+            .contains("<Part><PartNumber>3</PartNumber><ETag>&quot;3&quot;</ETag></Part>"));
+    }
+
+    #[tokio::test]
+    async fn resumable_upload_restart_test() {
+        let server = MockServer::start(resumable_server(Arc::new(AtomicBool::new(false)))).await;
+        let oss_instance = mock_client(&server);
+        let path = temp_path("resumable_restart");
+        let checkpoint_path = temp_path("resumable_restart_checkpoint");
+        tokio::fs::write(&path, vec![1u8; SIZE]).await.unwrap();
+        let file_mtime = DateTime::<Utc>::from(
+            tokio::fs::metadata(&path)
+                .await
+                .unwrap()
+                .modified()
+                .unwrap(),
+        );
+        let options = UploadOptions {
+            resumable: true,
+            checkpoint_path: Some(checkpoint_path.clone()),
+            ..multipart_options()
+        };
+        let stale = |upload_id: &str, file_mtime| UploadCheckpoint {
+            bucket: "bucket".to_owned(),
+            key: "big.bin".to_owned(),
+            upload_id: upload_id.to_owned(),
+            part_size: MIN_PART_SIZE,
+            file_size: SIZE as u64,
+            file_mtime,
+            parts: vec![CheckpointPart {
+                part_number: 1,
+                etag: "\"1\"".to_owned(),
+                crc64: None,
+            }],
+        };
+
+        // The upload no longer exists on the server
+        checkpoint::save(&checkpoint_path, &stale("gone", file_mtime))
+            .await
+            .unwrap();
+        oss_instance
+            .upload_file("big.bin", &path, options.clone())
+            .await
+            .unwrap();
+        let requests = server.requests();
+        assert_eq!(requests[0].param("uploadId").as_deref(), Some("gone"));
+        assert!(requests[1].param("uploads").is_some());
+        assert_eq!(requests.iter().filter(|r| r.method == "PUT").count(), 4);
+        let interrupted = requests.len();
+
+        // The file changed since
+        checkpoint::save(&checkpoint_path, &stale("changed", Utc::now()))
+            .await
+            .unwrap();
+        oss_instance
+            .upload_file("big.bin", &path, options.clone())
+            .await
+            .unwrap();
+        let requests = server.requests();
+        let changed = &requests[interrupted..];
+        assert_eq!(changed[0].method, "DELETE");
+        assert_eq!(changed[0].path, "/bucket/big.bin");
+        assert_eq!(changed[0].param("uploadId").as_deref(), Some("changed"));
+        assert!(changed[1].param("uploads").is_some());
+        assert!(checkpoint::load::<UploadCheckpoint>(&checkpoint_path)
+            .await
+            .is_none());
+
+        // The checkpoint is of another object, whose upload is left alone
+        let other = UploadCheckpoint {
+            bucket: "other-bucket".to_owned(),
+            key: "other.bin".to_owned(),
+            ..stale("other", file_mtime)
+        };
+        checkpoint::save(&checkpoint_path, &other).await.unwrap();
+        let err = oss_instance
+            .upload_file("big.bin", &path, options)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("other-bucket/other.bin"),
+            "{}",
+            err
+        );
+        assert_eq!(server.requests().len(), requests.len());
+        let kept: UploadCheckpoint = checkpoint::load(&checkpoint_path).await.unwrap();
+        assert_eq!(kept.upload_id, "other");
+        checkpoint::remove(&checkpoint_path).await.unwrap();
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+}
@@ -0,0 +1,37 @@
+//! (De)serialization of the XML bodies of OSS requests and responses.
+
+use std::str::FromStr;
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+
+use crate::{HttpResponse, Result};
+
+const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+
+pub(crate) fn from_slice<T: DeserializeOwned>(body: &[u8]) -> Result<T> {
+    Ok(quick_xml::de::from_reader(body)?)
+}
+
+/// Reads the whole body of `resp` and parses it.
+pub(crate) async fn from_response<T: DeserializeOwned>(resp: HttpResponse) -> Result<T> {
+    from_slice(&resp.collect().await?)
+}
+
+pub(crate) fn to_string<T: Serialize>(value: &T) -> Result<String> {
+    Ok(format!(
+        "{}{}",
+        XML_DECLARATION,
+        quick_xml::se::to_string(value)?
+    ))
+}
+
+/// Deserializes an attribute such as `StorageClass` from its name, to `None`
+/// when OSS sends one this SDK does not know yet.
+pub(crate) fn parse_lenient<'de, D, T>(d: D) -> std::result::Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+{
+    let s: Option<String> = Option::deserialize(d)?;
+    Ok(s.and_then(|s| s.parse().ok()))
+}